@@ -0,0 +1,82 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use rusty_ethereum::UInt256;
+
+const BATCH: usize = 64;
+
+// Deterministic xorshift so every run measures the same operands.
+fn next(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+// Operands that fit in a single limb exercise the fast paths; full-width
+// operands exercise the carry chains across all four limbs.
+fn operands(full_width: bool) -> Vec<(UInt256, UInt256)> {
+    let mut state = 0x9E37_79B9_7F4A_7C15;
+    let mut word = |limbs: usize| {
+        let mut bytes = [0u8; 32];
+        for chunk in bytes.chunks_mut(8).skip(4 - limbs) {
+            chunk.copy_from_slice(&next(&mut state).to_be_bytes());
+        }
+        UInt256::from(bytes)
+    };
+    (0..BATCH)
+        .map(|_| {
+            if full_width {
+                (word(4), word(2))
+            } else {
+                (word(1), word(1))
+            }
+        })
+        .collect()
+}
+
+fn bench_binary<F>(c: &mut Criterion, name: &str, op: F)
+where
+    F: Fn(UInt256, UInt256) -> UInt256,
+{
+    let mut group = c.benchmark_group(name);
+    group.throughput(Throughput::Elements(BATCH as u64));
+    for (label, full_width) in [("small", false), ("full", true)].iter() {
+        let inputs = operands(*full_width);
+        group.bench_function(*label, |b| {
+            b.iter(|| {
+                black_box(&inputs)
+                    .iter()
+                    .fold(UInt256::ZERO, |acc, (a, b)| acc ^ op(*a, *b))
+            })
+        });
+    }
+    group.finish();
+}
+
+fn arithmetic(c: &mut Criterion) {
+    bench_binary(c, "add", |a, b| a + b);
+    bench_binary(c, "sub", |a, b| a - b);
+    bench_binary(c, "mul", |a, b| a * b);
+    bench_binary(c, "div", |a, b| a / b);
+    bench_binary(c, "rem", |a, b| a % b);
+    bench_binary(c, "sdiv", |a, b| a.negate().signed_div(b));
+    bench_binary(c, "addmod", |a, b| a.add_mod(a, b));
+    bench_binary(c, "mulmod", |a, b| a.mul_mod(a, b));
+    bench_binary(c, "exp", |a, b| a.pow(b));
+}
+
+fn bitwise(c: &mut Criterion) {
+    bench_binary(c, "shl", |a, b| a << (b & UInt256::from(0xFFu8)));
+    bench_binary(c, "shr", |a, b| a >> (b & UInt256::from(0xFFu8)));
+    bench_binary(c, "sar", |a, b| a.negate().sar(b & UInt256::from(0xFFu8)));
+    bench_binary(c, "and", |a, b| a & b);
+}
+
+fn formatting(c: &mut Criterion) {
+    let max = !UInt256::ZERO;
+    c.bench_function("to_string_radix/10", |b| {
+        b.iter(|| black_box(max).to_string_radix(10))
+    });
+}
+
+criterion_group!(benches, arithmetic, bitwise, formatting);
+criterion_main!(benches);
@@ -1,11 +1,14 @@
-use crate::UInt256;
+use crate::{keccak256, Address, UInt256};
 
-use tiny_keccak::{Hasher, Keccak};
+// Mainnet's block gas limit, which bounds what any one call can spend.
+pub const BLOCK_GAS_LIMIT: u64 = 30_000_000;
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct Message {
     pub value: UInt256, // message funds in wei
-    pub caller: UInt256,
+    pub caller: Address,
+    pub address: Address, // the contract being called
+    pub gas_limit: u64,
     // First four bytes should be signature of method being called, e.g.
     // data[..4] = bytes4(keccak256(“add(uint256,uint256)”));
     pub data: Vec<u8>,
@@ -13,26 +16,24 @@ pub struct Message {
 
 fn method_signature(method_name: &str) -> [u8; 4] {
     //format is name(input,input,input...)
-    let mut full_hash = [0u8; 32];
-    let mut hasher = Keccak::v256();
-    hasher.update(method_name.as_bytes());
-    hasher.finalize(&mut full_hash);
+    let full_hash = keccak256(method_name.as_bytes());
 
     let mut data = [0u8; 4];
     println!("{}: {:02X?}", method_name, full_hash);
     data.copy_from_slice(&full_hash[..4]);
-    return data;
+    data
 }
 
 impl Message {
     pub fn new_call(method_name: &str) -> Message {
-        let mut data = vec![0u8; 32];
-        data[..4].copy_from_slice(&method_signature(method_name));
+        let data = method_signature(method_name).to_vec();
 
         Message {
             value: UInt256::ZERO, // Zero wei?
-            caller: UInt256::ZERO,
-            data: data,
+            caller: Address::ZERO,
+            address: Address::ZERO,
+            gas_limit: BLOCK_GAS_LIMIT,
+            data,
         }
     }
 }
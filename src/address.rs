@@ -0,0 +1,100 @@
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+use crate::UInt256;
+
+/// A 20-byte account address.
+#[derive(Default, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Address(pub [u8; 20]);
+
+impl Address {
+    pub const ZERO: Address = Address([0; 20]);
+
+    pub fn from_be_slice(bytes: &[u8]) -> Address {
+        Address::from(UInt256::from_be_slice(bytes))
+    }
+}
+
+// Like the EVM reading an address off the stack, this keeps only the low
+// 20 bytes of the word.
+impl From<UInt256> for Address {
+    fn from(word: UInt256) -> Address {
+        let bytes: [u8; 32] = word.into();
+        let mut address = [0u8; 20];
+        address.copy_from_slice(&bytes[12..]);
+        Address(address)
+    }
+}
+
+impl From<Address> for UInt256 {
+    fn from(address: Address) -> UInt256 {
+        UInt256::from_be_slice(&address.0)
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "0x")?;
+        for byte in &self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Address {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Address({})", self)
+    }
+}
+
+// Addresses serialize as 0x-prefixed hex, which also lets them key JSON
+// objects. Anything that doesn't fit in 20 bytes is rejected.
+impl Serialize for Address {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Address {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let word = UInt256::deserialize(deserializer)?;
+        let address = Address::from(word);
+        if UInt256::from(address) != word {
+            return Err(de::Error::custom(format!(
+                "{} is too long for an address",
+                word
+            )));
+        }
+        Ok(address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_low_twenty_bytes() {
+        let word = !UInt256::ZERO;
+        let address = Address::from(word);
+        assert_eq!(address, Address([0xFF; 20]));
+        assert_eq!(UInt256::from(address), word >> UInt256::from(96u8));
+        assert_eq!(
+            Address::from(UInt256::from(0xABu8)).to_string(),
+            "0x00000000000000000000000000000000000000ab"
+        );
+    }
+    #[test]
+    fn serde_round_trips() {
+        let address = Address([0xAB; 20]);
+        let json = serde_json::to_string(&address).expect("serializes");
+        assert_eq!(json, format!("\"{}\"", address));
+        assert_eq!(
+            serde_json::from_str::<Address>(&json).expect("parses"),
+            address
+        );
+        let too_long = format!("\"{:#x}\"", !UInt256::ZERO);
+        assert!(serde_json::from_str::<Address>(&too_long).is_err());
+    }
+}
@@ -0,0 +1,31 @@
+use crate::{Address, UInt256};
+
+/// The block a transaction executes in, as seen by COINBASE, TIMESTAMP,
+/// NUMBER, PREVRANDAO (formerly DIFFICULTY), GASLIMIT, BASEFEE, BLOBBASEFEE
+/// and CHAINID.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BlockEnv {
+    pub coinbase: Address,
+    pub timestamp: u64,
+    pub number: u64,
+    pub prevrandao: UInt256,
+    pub gas_limit: u64,
+    pub base_fee: UInt256,
+    pub blob_base_fee: UInt256,
+    pub chain_id: u64,
+}
+
+/// Per-transaction context shared by every call the transaction makes.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TxEnv {
+    pub origin: Address,
+    pub gas_price: UInt256,
+    // Versioned hashes of the transaction's blobs (EIP-4844).
+    pub blob_hashes: Vec<UInt256>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Env {
+    pub block: BlockEnv,
+    pub tx: TxEnv,
+}
@@ -13,145 +13,201 @@ pub struct Instruction {
     pub op: u8,
     pub name: &'static str,
     pub arg: ArgType,
+    // Static cost charged before the instruction runs. Memory expansion,
+    // storage writes and the like add their dynamic costs on top.
+    pub gas: u64,
 }
 
-pub const OP_STOP: Instruction = Instruction { op: 0x00, name: "STOP", arg: ArgType::Void };
-pub const OP_ADD: Instruction = Instruction { op: 0x01, name: "ADD", arg: ArgType::Void };
-pub const OP_MUL: Instruction = Instruction { op: 0x02, name: "MUL", arg: ArgType::Void };
-pub const OP_SUB: Instruction = Instruction { op: 0x03, name: "SUB", arg: ArgType::Void };
-pub const OP_DIV: Instruction = Instruction { op: 0x04, name: "DIV", arg: ArgType::Void };
-pub const OP_SDIV: Instruction = Instruction { op: 0x05, name: "SDIV", arg: ArgType::Void };
-pub const OP_MOD: Instruction = Instruction { op: 0x06, name: "MOD", arg: ArgType::Void };
-pub const OP_SMOD: Instruction = Instruction { op: 0x07, name: "SMOD", arg: ArgType::Void };
-pub const OP_ADDMOD: Instruction = Instruction { op: 0x08, name: "ADDMOD", arg: ArgType::Void };
-pub const OP_MULMOD: Instruction = Instruction { op: 0x09, name: "MULMOD", arg: ArgType::Void };
-pub const OP_EXP: Instruction = Instruction { op: 0x0a, name: "EXP", arg: ArgType::Void };
-pub const OP_SIGNEXTEND: Instruction = Instruction { op: 0x0b, name: "SIGNEXTEND", arg: ArgType::Void };
-pub const OP_LT: Instruction = Instruction { op: 0x10, name: "LT", arg: ArgType::Void };
-pub const OP_GT: Instruction = Instruction { op: 0x11, name: "GT", arg: ArgType::Void };
-pub const OP_SLT: Instruction = Instruction { op: 0x12, name: "SLT", arg: ArgType::Void };
-pub const OP_SGT: Instruction = Instruction { op: 0x13, name: "SGT", arg: ArgType::Void };
-pub const OP_EQ: Instruction = Instruction { op: 0x14, name: "EQ", arg: ArgType::Void };
-pub const OP_ISZERO: Instruction = Instruction { op: 0x15, name: "ISZERO", arg: ArgType::Void };
-pub const OP_AND: Instruction = Instruction { op: 0x16, name: "AND", arg: ArgType::Void };
-pub const OP_OR: Instruction = Instruction { op: 0x17, name: "OR", arg: ArgType::Void };
-pub const OP_XOR: Instruction = Instruction { op: 0x18, name: "XOR", arg: ArgType::Void };
-pub const OP_NOT: Instruction = Instruction { op: 0x19, name: "NOT", arg: ArgType::Void };
-pub const OP_BYTE: Instruction = Instruction { op: 0x1a, name: "BYTE", arg: ArgType::Void };
-pub const OP_SHL: Instruction = Instruction { op: 0x1b, name: "SHL", arg: ArgType::Void };
-pub const OP_SHR: Instruction = Instruction { op: 0x1c, name: "SHR", arg: ArgType::Void };
-pub const OP_SAR: Instruction = Instruction { op: 0x1d, name: "SAR", arg: ArgType::Void };
-pub const OP_SHA3: Instruction = Instruction { op: 0x20, name: "SHA3", arg: ArgType::Void };
-pub const OP_ADDRESS: Instruction = Instruction { op: 0x30, name: "ADDRESS", arg: ArgType::Void };
-pub const OP_BALANCE: Instruction = Instruction { op: 0x31, name: "BALANCE", arg: ArgType::Void };
-pub const OP_ORIGIN: Instruction = Instruction { op: 0x32, name: "ORIGIN", arg: ArgType::Void };
-pub const OP_CALLER: Instruction = Instruction { op: 0x33, name: "CALLER", arg: ArgType::Void };
-pub const OP_CALLVALUE: Instruction = Instruction { op: 0x34, name: "CALLVALUE", arg: ArgType::Void };
-pub const OP_CALLDATALOAD: Instruction = Instruction { op: 0x35, name: "CALLDATALOAD", arg: ArgType::Void };
-pub const OP_CALLDATASIZE: Instruction = Instruction { op: 0x36, name: "CALLDATASIZE", arg: ArgType::Void };
-pub const OP_CALLDATACOPY: Instruction = Instruction { op: 0x37, name: "CALLDATACOPY", arg: ArgType::Void };
-pub const OP_CODESIZE: Instruction = Instruction { op: 0x38, name: "CODESIZE", arg: ArgType::Void };
-pub const OP_CODECOPY: Instruction = Instruction { op: 0x39, name: "CODECOPY", arg: ArgType::Void };
-pub const OP_GASPRICE: Instruction = Instruction { op: 0x3a, name: "GASPRICE", arg: ArgType::Void };
-pub const OP_EXTCODESIZE: Instruction = Instruction { op: 0x3b, name: "EXTCODESIZE", arg: ArgType::Void };
-pub const OP_EXTCODECOPY: Instruction = Instruction { op: 0x3c, name: "EXTCODECOPY", arg: ArgType::Void };
-pub const OP_BLOCKHASH: Instruction = Instruction { op: 0x40, name: "BLOCKHASH", arg: ArgType::Void };
-pub const OP_COINBASE: Instruction = Instruction { op: 0x41, name: "COINBASE", arg: ArgType::Void };
-pub const OP_TIMESTAMP: Instruction = Instruction { op: 0x42, name: "TIMESTAMP", arg: ArgType::Void };
-pub const OP_NUMBER: Instruction = Instruction { op: 0x43, name: "NUMBER", arg: ArgType::Void };
-pub const OP_DIFFICULTY: Instruction = Instruction { op: 0x44, name: "DIFFICULTY", arg: ArgType::Void };
-pub const OP_GASLIMIT: Instruction = Instruction { op: 0x45, name: "GASLIMIT", arg: ArgType::Void };
-pub const OP_POP: Instruction = Instruction { op: 0x50, name: "POP", arg: ArgType::Void };
-pub const OP_MLOAD: Instruction = Instruction { op: 0x51, name: "MLOAD", arg: ArgType::Void };
-pub const OP_MSTORE: Instruction = Instruction { op: 0x52, name: "MSTORE", arg: ArgType::Void };
-pub const OP_MSTORE8: Instruction = Instruction { op: 0x53, name: "MSTORE8", arg: ArgType::Void };
-pub const OP_SLOAD: Instruction = Instruction { op: 0x54, name: "SLOAD", arg: ArgType::Void };
-pub const OP_SSTORE: Instruction = Instruction { op: 0x55, name: "SSTORE", arg: ArgType::Void };
-pub const OP_JUMP: Instruction = Instruction { op: 0x56, name: "JUMP", arg: ArgType::Void };
-pub const OP_JUMPI: Instruction = Instruction { op: 0x57, name: "JUMPI", arg: ArgType::Void };
-pub const OP_PC: Instruction = Instruction { op: 0x58, name: "PC", arg: ArgType::Void };
-pub const OP_MSIZE: Instruction = Instruction { op: 0x59, name: "MSIZE", arg: ArgType::Void };
-pub const OP_GAS: Instruction = Instruction { op: 0x5a, name: "GAS", arg: ArgType::Void };
-pub const OP_JUMPDEST: Instruction = Instruction { op: 0x5b, name: "JUMPDEST", arg: ArgType::Void };
-pub const OP_PUSH1: Instruction = Instruction { op: 0x60, name: "PUSH1", arg: ArgType::U(8) };
-pub const OP_PUSH2: Instruction = Instruction { op: 0x61, name: "PUSH2", arg: ArgType::U(16) };
-pub const OP_PUSH3: Instruction = Instruction { op: 0x62, name: "PUSH3", arg: ArgType::U(24) };
-pub const OP_PUSH4: Instruction = Instruction { op: 0x63, name: "PUSH4", arg: ArgType::U(32) };
-pub const OP_PUSH5: Instruction = Instruction { op: 0x64, name: "PUSH5", arg: ArgType::U(40) };
-pub const OP_PUSH6: Instruction = Instruction { op: 0x65, name: "PUSH6", arg: ArgType::U(48) };
-pub const OP_PUSH7: Instruction = Instruction { op: 0x66, name: "PUSH7", arg: ArgType::U(56) };
-pub const OP_PUSH8: Instruction = Instruction { op: 0x67, name: "PUSH8", arg: ArgType::U(64) };
-pub const OP_PUSH9: Instruction = Instruction { op: 0x68, name: "PUSH9", arg: ArgType::U(72) };
-pub const OP_PUSH10: Instruction = Instruction { op: 0x69, name: "PUSH10", arg: ArgType::U(80) };
-pub const OP_PUSH11: Instruction = Instruction { op: 0x6A, name: "PUSH11", arg: ArgType::U(88) };
-pub const OP_PUSH12: Instruction = Instruction { op: 0x6B, name: "PUSH12", arg: ArgType::U(96) };
-pub const OP_PUSH13: Instruction = Instruction { op: 0x6C, name: "PUSH13", arg: ArgType::U(104) };
-pub const OP_PUSH14: Instruction = Instruction { op: 0x6D, name: "PUSH14", arg: ArgType::U(112) };
-pub const OP_PUSH15: Instruction = Instruction { op: 0x6E, name: "PUSH15", arg: ArgType::U(120) };
-pub const OP_PUSH16: Instruction = Instruction { op: 0x6F, name: "PUSH16", arg: ArgType::U(128) };
-pub const OP_PUSH17: Instruction = Instruction { op: 0x70, name: "PUSH17", arg: ArgType::U(136) };
-pub const OP_PUSH18: Instruction = Instruction { op: 0x71, name: "PUSH18", arg: ArgType::U(144) };
-pub const OP_PUSH19: Instruction = Instruction { op: 0x72, name: "PUSH19", arg: ArgType::U(152) };
-pub const OP_PUSH20: Instruction = Instruction { op: 0x73, name: "PUSH20", arg: ArgType::U(160) };
-pub const OP_PUSH21: Instruction = Instruction { op: 0x74, name: "PUSH21", arg: ArgType::U(168) };
-pub const OP_PUSH22: Instruction = Instruction { op: 0x75, name: "PUSH22", arg: ArgType::U(176) };
-pub const OP_PUSH23: Instruction = Instruction { op: 0x76, name: "PUSH23", arg: ArgType::U(184) };
-pub const OP_PUSH24: Instruction = Instruction { op: 0x77, name: "PUSH24", arg: ArgType::U(192) };
-pub const OP_PUSH25: Instruction = Instruction { op: 0x78, name: "PUSH25", arg: ArgType::U(200) };
-pub const OP_PUSH26: Instruction = Instruction { op: 0x79, name: "PUSH26", arg: ArgType::U(208) };
-pub const OP_PUSH27: Instruction = Instruction { op: 0x7A, name: "PUSH27", arg: ArgType::U(216) };
-pub const OP_PUSH28: Instruction = Instruction { op: 0x7B, name: "PUSH28", arg: ArgType::U(224) };
-pub const OP_PUSH29: Instruction = Instruction { op: 0x7C, name: "PUSH29", arg: ArgType::U(232) };
-pub const OP_PUSH30: Instruction = Instruction { op: 0x7D, name: "PUSH30", arg: ArgType::U(240) };
-pub const OP_PUSH31: Instruction = Instruction { op: 0x7E, name: "PUSH31", arg: ArgType::U(248) };
-pub const OP_PUSH32: Instruction = Instruction { op: 0x7F, name: "PUSH32", arg: ArgType::U(256) };
-pub const OP_DUP1: Instruction = Instruction { op: 0x80, name: "DUP1", arg: ArgType::Void };
-pub const OP_DUP2: Instruction = Instruction { op: 0x81, name: "DUP2", arg: ArgType::Void };
-pub const OP_DUP3: Instruction = Instruction { op: 0x82, name: "DUP3", arg: ArgType::Void };
-pub const OP_DUP4: Instruction = Instruction { op: 0x83, name: "DUP4", arg: ArgType::Void };
-pub const OP_DUP5: Instruction = Instruction { op: 0x84, name: "DUP5", arg: ArgType::Void };
-pub const OP_DUP6: Instruction = Instruction { op: 0x85, name: "DUP6", arg: ArgType::Void };
-pub const OP_DUP7: Instruction = Instruction { op: 0x86, name: "DUP7", arg: ArgType::Void };
-pub const OP_DUP8: Instruction = Instruction { op: 0x87, name: "DUP8", arg: ArgType::Void };
-pub const OP_DUP9: Instruction = Instruction { op: 0x88, name: "DUP9", arg: ArgType::Void };
-pub const OP_DUP10: Instruction = Instruction { op: 0x89, name: "DUP10", arg: ArgType::Void };
-pub const OP_DUP11: Instruction = Instruction { op: 0x8A, name: "DUP11", arg: ArgType::Void };
-pub const OP_DUP12: Instruction = Instruction { op: 0x8B, name: "DUP12", arg: ArgType::Void };
-pub const OP_DUP13: Instruction = Instruction { op: 0x8C, name: "DUP13", arg: ArgType::Void };
-pub const OP_DUP14: Instruction = Instruction { op: 0x8D, name: "DUP14", arg: ArgType::Void };
-pub const OP_DUP15: Instruction = Instruction { op: 0x8E, name: "DUP15", arg: ArgType::Void };
-pub const OP_DUP16: Instruction = Instruction { op: 0x8F, name: "DUP16", arg: ArgType::Void };
-pub const OP_SWAP1: Instruction = Instruction { op: 0x90, name: "SWAP1", arg: ArgType::Void };
-pub const OP_SWAP2: Instruction = Instruction { op: 0x91, name: "SWAP2", arg: ArgType::Void };
-pub const OP_SWAP3: Instruction = Instruction { op: 0x92, name: "SWAP3", arg: ArgType::Void };
-pub const OP_SWAP4: Instruction = Instruction { op: 0x93, name: "SWAP4", arg: ArgType::Void };
-pub const OP_SWAP5: Instruction = Instruction { op: 0x94, name: "SWAP5", arg: ArgType::Void };
-pub const OP_SWAP6: Instruction = Instruction { op: 0x95, name: "SWAP6", arg: ArgType::Void };
-pub const OP_SWAP7: Instruction = Instruction { op: 0x96, name: "SWAP7", arg: ArgType::Void };
-pub const OP_SWAP8: Instruction = Instruction { op: 0x97, name: "SWAP8", arg: ArgType::Void };
-pub const OP_SWAP9: Instruction = Instruction { op: 0x98, name: "SWAP9", arg: ArgType::Void };
-pub const OP_SWAP10: Instruction = Instruction { op: 0x99, name: "SWAP10", arg: ArgType::Void };
-pub const OP_SWAP11: Instruction = Instruction { op: 0x9A, name: "SWAP11", arg: ArgType::Void };
-pub const OP_SWAP12: Instruction = Instruction { op: 0x9B, name: "SWAP12", arg: ArgType::Void };
-pub const OP_SWAP13: Instruction = Instruction { op: 0x9C, name: "SWAP13", arg: ArgType::Void };
-pub const OP_SWAP14: Instruction = Instruction { op: 0x9D, name: "SWAP14", arg: ArgType::Void };
-pub const OP_SWAP15: Instruction = Instruction { op: 0x9E, name: "SWAP15", arg: ArgType::Void };
-pub const OP_SWAP16: Instruction = Instruction { op: 0x9F, name: "SWAP16", arg: ArgType::Void };
-pub const OP_LOG0: Instruction = Instruction { op: 0xa0, name: "LOG0", arg: ArgType::Void };
-pub const OP_LOG1: Instruction = Instruction { op: 0xa1, name: "LOG1", arg: ArgType::Void };
-pub const OP_LOG2: Instruction = Instruction { op: 0xa2, name: "LOG2", arg: ArgType::Void };
-pub const OP_LOG3: Instruction = Instruction { op: 0xa3, name: "LOG3", arg: ArgType::Void };
-pub const OP_LOG4: Instruction = Instruction { op: 0xa4, name: "LOG4", arg: ArgType::Void };
-pub const OP_CREATE: Instruction = Instruction { op: 0xf0, name: "CREATE", arg: ArgType::Void };
-pub const OP_CALL: Instruction = Instruction { op: 0xf1, name: "CALL", arg: ArgType::Void };
-pub const OP_CALLCODE: Instruction = Instruction { op: 0xf2, name: "CALLCODE", arg: ArgType::Void };
-pub const OP_RETURN: Instruction = Instruction { op: 0xf3, name: "RETURN", arg: ArgType::Void };
-pub const OP_DELEGATECALL: Instruction = Instruction { op: 0xf4, name: "DELEGATECALL", arg: ArgType::Void };
-pub const OP_REVERT: Instruction = Instruction { op: 0xfd, name: "REVERT", arg: ArgType::Void };
-pub const OP_INVALID: Instruction = Instruction { op: 0xfe, name: "INVALID", arg: ArgType::Void };
-pub const OP_SELFDESTRUCT: Instruction = Instruction { op: 0xff, name: "SELFDESTRUCT", arg: ArgType::Void };
+// Gas tiers, named as in the Yellow Paper (appendix G), at their Cancun
+// values. Accounts and storage slots cost G_WARMACCESS once touched and
+// the cold price the first time in a transaction (EIP-2929), and SSTORE
+// is priced against the slot's original value (EIP-2200, EIP-3529).
+pub const G_ZERO: u64 = 0;
+pub const G_JUMPDEST: u64 = 1;
+pub const G_BASE: u64 = 2;
+pub const G_VERYLOW: u64 = 3;
+pub const G_LOW: u64 = 5;
+pub const G_MID: u64 = 8;
+pub const G_HIGH: u64 = 10;
+pub const G_EXP: u64 = 10;
+pub const G_EXPBYTE: u64 = 50;
+pub const G_MEMORY: u64 = 3;
+pub const G_COPY: u64 = 3;
+pub const G_SHA3: u64 = 30;
+pub const G_SHA3WORD: u64 = 6;
+pub const G_BLOCKHASH: u64 = 20;
+pub const G_WARMACCESS: u64 = 100;
+pub const G_COLDACCOUNTACCESS: u64 = 2600;
+pub const G_COLDSLOAD: u64 = 2100;
+pub const G_SSET: u64 = 20000;
+pub const G_SRESET: u64 = 2900;
+pub const R_SCLEAR: u64 = 4800;
+pub const G_LOG: u64 = 375;
+pub const G_LOGTOPIC: u64 = 375;
+pub const G_LOGDATA: u64 = 8;
+pub const G_CREATE: u64 = 32000;
+pub const G_CODEDEPOSIT: u64 = 200;
+pub const G_INITCODEWORD: u64 = 2;
+pub const G_CALLVALUE: u64 = 9000;
+pub const G_CALLSTIPEND: u64 = 2300;
+pub const G_NEWACCOUNT: u64 = 25000;
+pub const G_SELFDESTRUCT: u64 = 5000;
+pub const G_TRANSACTION: u64 = 21000;
+pub const G_TXCREATE: u64 = 32000;
+pub const G_TXDATAZERO: u64 = 4;
+pub const G_TXDATANONZERO: u64 = 16;
 
-pub const INSTRUCTIONS: [Instruction; 135] = [
+pub const OP_STOP: Instruction = Instruction { op: 0x00, name: "STOP", arg: ArgType::Void, gas: G_ZERO };
+pub const OP_ADD: Instruction = Instruction { op: 0x01, name: "ADD", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_MUL: Instruction = Instruction { op: 0x02, name: "MUL", arg: ArgType::Void, gas: G_LOW };
+pub const OP_SUB: Instruction = Instruction { op: 0x03, name: "SUB", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_DIV: Instruction = Instruction { op: 0x04, name: "DIV", arg: ArgType::Void, gas: G_LOW };
+pub const OP_SDIV: Instruction = Instruction { op: 0x05, name: "SDIV", arg: ArgType::Void, gas: G_LOW };
+pub const OP_MOD: Instruction = Instruction { op: 0x06, name: "MOD", arg: ArgType::Void, gas: G_LOW };
+pub const OP_SMOD: Instruction = Instruction { op: 0x07, name: "SMOD", arg: ArgType::Void, gas: G_LOW };
+pub const OP_ADDMOD: Instruction = Instruction { op: 0x08, name: "ADDMOD", arg: ArgType::Void, gas: G_MID };
+pub const OP_MULMOD: Instruction = Instruction { op: 0x09, name: "MULMOD", arg: ArgType::Void, gas: G_MID };
+pub const OP_EXP: Instruction = Instruction { op: 0x0a, name: "EXP", arg: ArgType::Void, gas: G_EXP };
+pub const OP_SIGNEXTEND: Instruction = Instruction { op: 0x0b, name: "SIGNEXTEND", arg: ArgType::Void, gas: G_LOW };
+pub const OP_LT: Instruction = Instruction { op: 0x10, name: "LT", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_GT: Instruction = Instruction { op: 0x11, name: "GT", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_SLT: Instruction = Instruction { op: 0x12, name: "SLT", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_SGT: Instruction = Instruction { op: 0x13, name: "SGT", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_EQ: Instruction = Instruction { op: 0x14, name: "EQ", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_ISZERO: Instruction = Instruction { op: 0x15, name: "ISZERO", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_AND: Instruction = Instruction { op: 0x16, name: "AND", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_OR: Instruction = Instruction { op: 0x17, name: "OR", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_XOR: Instruction = Instruction { op: 0x18, name: "XOR", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_NOT: Instruction = Instruction { op: 0x19, name: "NOT", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_BYTE: Instruction = Instruction { op: 0x1a, name: "BYTE", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_SHL: Instruction = Instruction { op: 0x1b, name: "SHL", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_SHR: Instruction = Instruction { op: 0x1c, name: "SHR", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_SAR: Instruction = Instruction { op: 0x1d, name: "SAR", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_SHA3: Instruction = Instruction { op: 0x20, name: "SHA3", arg: ArgType::Void, gas: G_SHA3 };
+pub const OP_ADDRESS: Instruction = Instruction { op: 0x30, name: "ADDRESS", arg: ArgType::Void, gas: G_BASE };
+pub const OP_BALANCE: Instruction = Instruction { op: 0x31, name: "BALANCE", arg: ArgType::Void, gas: G_WARMACCESS };
+pub const OP_ORIGIN: Instruction = Instruction { op: 0x32, name: "ORIGIN", arg: ArgType::Void, gas: G_BASE };
+pub const OP_CALLER: Instruction = Instruction { op: 0x33, name: "CALLER", arg: ArgType::Void, gas: G_BASE };
+pub const OP_CALLVALUE: Instruction = Instruction { op: 0x34, name: "CALLVALUE", arg: ArgType::Void, gas: G_BASE };
+pub const OP_CALLDATALOAD: Instruction = Instruction { op: 0x35, name: "CALLDATALOAD", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_CALLDATASIZE: Instruction = Instruction { op: 0x36, name: "CALLDATASIZE", arg: ArgType::Void, gas: G_BASE };
+pub const OP_CALLDATACOPY: Instruction = Instruction { op: 0x37, name: "CALLDATACOPY", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_CODESIZE: Instruction = Instruction { op: 0x38, name: "CODESIZE", arg: ArgType::Void, gas: G_BASE };
+pub const OP_CODECOPY: Instruction = Instruction { op: 0x39, name: "CODECOPY", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_GASPRICE: Instruction = Instruction { op: 0x3a, name: "GASPRICE", arg: ArgType::Void, gas: G_BASE };
+pub const OP_EXTCODESIZE: Instruction = Instruction { op: 0x3b, name: "EXTCODESIZE", arg: ArgType::Void, gas: G_WARMACCESS };
+pub const OP_EXTCODECOPY: Instruction = Instruction { op: 0x3c, name: "EXTCODECOPY", arg: ArgType::Void, gas: G_WARMACCESS };
+pub const OP_RETURNDATASIZE: Instruction = Instruction { op: 0x3d, name: "RETURNDATASIZE", arg: ArgType::Void, gas: G_BASE };
+pub const OP_RETURNDATACOPY: Instruction = Instruction { op: 0x3e, name: "RETURNDATACOPY", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_EXTCODEHASH: Instruction = Instruction { op: 0x3f, name: "EXTCODEHASH", arg: ArgType::Void, gas: G_WARMACCESS };
+pub const OP_BLOCKHASH: Instruction = Instruction { op: 0x40, name: "BLOCKHASH", arg: ArgType::Void, gas: G_BLOCKHASH };
+pub const OP_COINBASE: Instruction = Instruction { op: 0x41, name: "COINBASE", arg: ArgType::Void, gas: G_BASE };
+pub const OP_TIMESTAMP: Instruction = Instruction { op: 0x42, name: "TIMESTAMP", arg: ArgType::Void, gas: G_BASE };
+pub const OP_NUMBER: Instruction = Instruction { op: 0x43, name: "NUMBER", arg: ArgType::Void, gas: G_BASE };
+pub const OP_DIFFICULTY: Instruction = Instruction { op: 0x44, name: "DIFFICULTY", arg: ArgType::Void, gas: G_BASE };
+pub const OP_GASLIMIT: Instruction = Instruction { op: 0x45, name: "GASLIMIT", arg: ArgType::Void, gas: G_BASE };
+pub const OP_CHAINID: Instruction = Instruction { op: 0x46, name: "CHAINID", arg: ArgType::Void, gas: G_BASE };
+pub const OP_SELFBALANCE: Instruction = Instruction { op: 0x47, name: "SELFBALANCE", arg: ArgType::Void, gas: G_LOW };
+pub const OP_BASEFEE: Instruction = Instruction { op: 0x48, name: "BASEFEE", arg: ArgType::Void, gas: G_BASE };
+pub const OP_BLOBHASH: Instruction = Instruction { op: 0x49, name: "BLOBHASH", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_BLOBBASEFEE: Instruction = Instruction { op: 0x4a, name: "BLOBBASEFEE", arg: ArgType::Void, gas: G_BASE };
+pub const OP_POP: Instruction = Instruction { op: 0x50, name: "POP", arg: ArgType::Void, gas: G_BASE };
+pub const OP_MLOAD: Instruction = Instruction { op: 0x51, name: "MLOAD", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_MSTORE: Instruction = Instruction { op: 0x52, name: "MSTORE", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_MSTORE8: Instruction = Instruction { op: 0x53, name: "MSTORE8", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_SLOAD: Instruction = Instruction { op: 0x54, name: "SLOAD", arg: ArgType::Void, gas: G_WARMACCESS };
+pub const OP_SSTORE: Instruction = Instruction { op: 0x55, name: "SSTORE", arg: ArgType::Void, gas: G_ZERO };
+pub const OP_JUMP: Instruction = Instruction { op: 0x56, name: "JUMP", arg: ArgType::Void, gas: G_MID };
+pub const OP_JUMPI: Instruction = Instruction { op: 0x57, name: "JUMPI", arg: ArgType::Void, gas: G_HIGH };
+pub const OP_PC: Instruction = Instruction { op: 0x58, name: "PC", arg: ArgType::Void, gas: G_BASE };
+pub const OP_MSIZE: Instruction = Instruction { op: 0x59, name: "MSIZE", arg: ArgType::Void, gas: G_BASE };
+pub const OP_GAS: Instruction = Instruction { op: 0x5a, name: "GAS", arg: ArgType::Void, gas: G_BASE };
+pub const OP_JUMPDEST: Instruction = Instruction { op: 0x5b, name: "JUMPDEST", arg: ArgType::Void, gas: G_JUMPDEST };
+pub const OP_TLOAD: Instruction = Instruction { op: 0x5c, name: "TLOAD", arg: ArgType::Void, gas: G_WARMACCESS };
+pub const OP_TSTORE: Instruction = Instruction { op: 0x5d, name: "TSTORE", arg: ArgType::Void, gas: G_WARMACCESS };
+pub const OP_MCOPY: Instruction = Instruction { op: 0x5e, name: "MCOPY", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_PUSH0: Instruction = Instruction { op: 0x5f, name: "PUSH0", arg: ArgType::Void, gas: G_BASE };
+pub const OP_PUSH1: Instruction = Instruction { op: 0x60, name: "PUSH1", arg: ArgType::U(8), gas: G_VERYLOW };
+pub const OP_PUSH2: Instruction = Instruction { op: 0x61, name: "PUSH2", arg: ArgType::U(16), gas: G_VERYLOW };
+pub const OP_PUSH3: Instruction = Instruction { op: 0x62, name: "PUSH3", arg: ArgType::U(24), gas: G_VERYLOW };
+pub const OP_PUSH4: Instruction = Instruction { op: 0x63, name: "PUSH4", arg: ArgType::U(32), gas: G_VERYLOW };
+pub const OP_PUSH5: Instruction = Instruction { op: 0x64, name: "PUSH5", arg: ArgType::U(40), gas: G_VERYLOW };
+pub const OP_PUSH6: Instruction = Instruction { op: 0x65, name: "PUSH6", arg: ArgType::U(48), gas: G_VERYLOW };
+pub const OP_PUSH7: Instruction = Instruction { op: 0x66, name: "PUSH7", arg: ArgType::U(56), gas: G_VERYLOW };
+pub const OP_PUSH8: Instruction = Instruction { op: 0x67, name: "PUSH8", arg: ArgType::U(64), gas: G_VERYLOW };
+pub const OP_PUSH9: Instruction = Instruction { op: 0x68, name: "PUSH9", arg: ArgType::U(72), gas: G_VERYLOW };
+pub const OP_PUSH10: Instruction = Instruction { op: 0x69, name: "PUSH10", arg: ArgType::U(80), gas: G_VERYLOW };
+pub const OP_PUSH11: Instruction = Instruction { op: 0x6A, name: "PUSH11", arg: ArgType::U(88), gas: G_VERYLOW };
+pub const OP_PUSH12: Instruction = Instruction { op: 0x6B, name: "PUSH12", arg: ArgType::U(96), gas: G_VERYLOW };
+pub const OP_PUSH13: Instruction = Instruction { op: 0x6C, name: "PUSH13", arg: ArgType::U(104), gas: G_VERYLOW };
+pub const OP_PUSH14: Instruction = Instruction { op: 0x6D, name: "PUSH14", arg: ArgType::U(112), gas: G_VERYLOW };
+pub const OP_PUSH15: Instruction = Instruction { op: 0x6E, name: "PUSH15", arg: ArgType::U(120), gas: G_VERYLOW };
+pub const OP_PUSH16: Instruction = Instruction { op: 0x6F, name: "PUSH16", arg: ArgType::U(128), gas: G_VERYLOW };
+pub const OP_PUSH17: Instruction = Instruction { op: 0x70, name: "PUSH17", arg: ArgType::U(136), gas: G_VERYLOW };
+pub const OP_PUSH18: Instruction = Instruction { op: 0x71, name: "PUSH18", arg: ArgType::U(144), gas: G_VERYLOW };
+pub const OP_PUSH19: Instruction = Instruction { op: 0x72, name: "PUSH19", arg: ArgType::U(152), gas: G_VERYLOW };
+pub const OP_PUSH20: Instruction = Instruction { op: 0x73, name: "PUSH20", arg: ArgType::U(160), gas: G_VERYLOW };
+pub const OP_PUSH21: Instruction = Instruction { op: 0x74, name: "PUSH21", arg: ArgType::U(168), gas: G_VERYLOW };
+pub const OP_PUSH22: Instruction = Instruction { op: 0x75, name: "PUSH22", arg: ArgType::U(176), gas: G_VERYLOW };
+pub const OP_PUSH23: Instruction = Instruction { op: 0x76, name: "PUSH23", arg: ArgType::U(184), gas: G_VERYLOW };
+pub const OP_PUSH24: Instruction = Instruction { op: 0x77, name: "PUSH24", arg: ArgType::U(192), gas: G_VERYLOW };
+pub const OP_PUSH25: Instruction = Instruction { op: 0x78, name: "PUSH25", arg: ArgType::U(200), gas: G_VERYLOW };
+pub const OP_PUSH26: Instruction = Instruction { op: 0x79, name: "PUSH26", arg: ArgType::U(208), gas: G_VERYLOW };
+pub const OP_PUSH27: Instruction = Instruction { op: 0x7A, name: "PUSH27", arg: ArgType::U(216), gas: G_VERYLOW };
+pub const OP_PUSH28: Instruction = Instruction { op: 0x7B, name: "PUSH28", arg: ArgType::U(224), gas: G_VERYLOW };
+pub const OP_PUSH29: Instruction = Instruction { op: 0x7C, name: "PUSH29", arg: ArgType::U(232), gas: G_VERYLOW };
+pub const OP_PUSH30: Instruction = Instruction { op: 0x7D, name: "PUSH30", arg: ArgType::U(240), gas: G_VERYLOW };
+pub const OP_PUSH31: Instruction = Instruction { op: 0x7E, name: "PUSH31", arg: ArgType::U(248), gas: G_VERYLOW };
+pub const OP_PUSH32: Instruction = Instruction { op: 0x7F, name: "PUSH32", arg: ArgType::U(256), gas: G_VERYLOW };
+pub const OP_DUP1: Instruction = Instruction { op: 0x80, name: "DUP1", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_DUP2: Instruction = Instruction { op: 0x81, name: "DUP2", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_DUP3: Instruction = Instruction { op: 0x82, name: "DUP3", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_DUP4: Instruction = Instruction { op: 0x83, name: "DUP4", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_DUP5: Instruction = Instruction { op: 0x84, name: "DUP5", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_DUP6: Instruction = Instruction { op: 0x85, name: "DUP6", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_DUP7: Instruction = Instruction { op: 0x86, name: "DUP7", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_DUP8: Instruction = Instruction { op: 0x87, name: "DUP8", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_DUP9: Instruction = Instruction { op: 0x88, name: "DUP9", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_DUP10: Instruction = Instruction { op: 0x89, name: "DUP10", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_DUP11: Instruction = Instruction { op: 0x8A, name: "DUP11", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_DUP12: Instruction = Instruction { op: 0x8B, name: "DUP12", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_DUP13: Instruction = Instruction { op: 0x8C, name: "DUP13", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_DUP14: Instruction = Instruction { op: 0x8D, name: "DUP14", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_DUP15: Instruction = Instruction { op: 0x8E, name: "DUP15", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_DUP16: Instruction = Instruction { op: 0x8F, name: "DUP16", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_SWAP1: Instruction = Instruction { op: 0x90, name: "SWAP1", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_SWAP2: Instruction = Instruction { op: 0x91, name: "SWAP2", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_SWAP3: Instruction = Instruction { op: 0x92, name: "SWAP3", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_SWAP4: Instruction = Instruction { op: 0x93, name: "SWAP4", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_SWAP5: Instruction = Instruction { op: 0x94, name: "SWAP5", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_SWAP6: Instruction = Instruction { op: 0x95, name: "SWAP6", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_SWAP7: Instruction = Instruction { op: 0x96, name: "SWAP7", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_SWAP8: Instruction = Instruction { op: 0x97, name: "SWAP8", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_SWAP9: Instruction = Instruction { op: 0x98, name: "SWAP9", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_SWAP10: Instruction = Instruction { op: 0x99, name: "SWAP10", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_SWAP11: Instruction = Instruction { op: 0x9A, name: "SWAP11", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_SWAP12: Instruction = Instruction { op: 0x9B, name: "SWAP12", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_SWAP13: Instruction = Instruction { op: 0x9C, name: "SWAP13", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_SWAP14: Instruction = Instruction { op: 0x9D, name: "SWAP14", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_SWAP15: Instruction = Instruction { op: 0x9E, name: "SWAP15", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_SWAP16: Instruction = Instruction { op: 0x9F, name: "SWAP16", arg: ArgType::Void, gas: G_VERYLOW };
+pub const OP_LOG0: Instruction = Instruction { op: 0xa0, name: "LOG0", arg: ArgType::Void, gas: G_LOG };
+pub const OP_LOG1: Instruction = Instruction { op: 0xa1, name: "LOG1", arg: ArgType::Void, gas: G_LOG };
+pub const OP_LOG2: Instruction = Instruction { op: 0xa2, name: "LOG2", arg: ArgType::Void, gas: G_LOG };
+pub const OP_LOG3: Instruction = Instruction { op: 0xa3, name: "LOG3", arg: ArgType::Void, gas: G_LOG };
+pub const OP_LOG4: Instruction = Instruction { op: 0xa4, name: "LOG4", arg: ArgType::Void, gas: G_LOG };
+pub const OP_CREATE: Instruction = Instruction { op: 0xf0, name: "CREATE", arg: ArgType::Void, gas: G_CREATE };
+pub const OP_CALL: Instruction = Instruction { op: 0xf1, name: "CALL", arg: ArgType::Void, gas: G_WARMACCESS };
+pub const OP_CALLCODE: Instruction = Instruction { op: 0xf2, name: "CALLCODE", arg: ArgType::Void, gas: G_WARMACCESS };
+pub const OP_RETURN: Instruction = Instruction { op: 0xf3, name: "RETURN", arg: ArgType::Void, gas: G_ZERO };
+pub const OP_DELEGATECALL: Instruction = Instruction { op: 0xf4, name: "DELEGATECALL", arg: ArgType::Void, gas: G_WARMACCESS };
+pub const OP_CREATE2: Instruction = Instruction { op: 0xf5, name: "CREATE2", arg: ArgType::Void, gas: G_CREATE };
+pub const OP_STATICCALL: Instruction = Instruction { op: 0xfa, name: "STATICCALL", arg: ArgType::Void, gas: G_WARMACCESS };
+pub const OP_REVERT: Instruction = Instruction { op: 0xfd, name: "REVERT", arg: ArgType::Void, gas: G_ZERO };
+pub const OP_INVALID: Instruction = Instruction { op: 0xfe, name: "INVALID", arg: ArgType::Void, gas: G_ZERO };
+pub const OP_SELFDESTRUCT: Instruction = Instruction { op: 0xff, name: "SELFDESTRUCT", arg: ArgType::Void, gas: G_SELFDESTRUCT };
+
+pub const INSTRUCTIONS: [Instruction; 149] = [
     OP_STOP,
     OP_ADD,
     OP_MUL,
@@ -192,12 +248,20 @@ pub const INSTRUCTIONS: [Instruction; 135] = [
     OP_GASPRICE,
     OP_EXTCODESIZE,
     OP_EXTCODECOPY,
+    OP_RETURNDATASIZE,
+    OP_RETURNDATACOPY,
+    OP_EXTCODEHASH,
     OP_BLOCKHASH,
     OP_COINBASE,
     OP_TIMESTAMP,
     OP_NUMBER,
     OP_DIFFICULTY,
     OP_GASLIMIT,
+    OP_CHAINID,
+    OP_SELFBALANCE,
+    OP_BASEFEE,
+    OP_BLOBHASH,
+    OP_BLOBBASEFEE,
     OP_POP,
     OP_MLOAD,
     OP_MSTORE,
@@ -210,6 +274,10 @@ pub const INSTRUCTIONS: [Instruction; 135] = [
     OP_MSIZE,
     OP_GAS,
     OP_JUMPDEST,
+    OP_TLOAD,
+    OP_TSTORE,
+    OP_MCOPY,
+    OP_PUSH0,
     OP_PUSH1,
     OP_PUSH2,
     OP_PUSH3,
@@ -284,6 +352,8 @@ pub const INSTRUCTIONS: [Instruction; 135] = [
     OP_CALLCODE,
     OP_RETURN,
     OP_DELEGATECALL,
+    OP_CREATE2,
+    OP_STATICCALL,
     OP_REVERT,
     OP_INVALID,
     OP_SELFDESTRUCT,
@@ -0,0 +1,25 @@
+use tiny_keccak::{Hasher, Keccak};
+
+pub fn keccak256(bytes: &[u8]) -> [u8; 32] {
+    let mut hash = [0u8; 32];
+    let mut hasher = Keccak::v256();
+    hasher.update(bytes);
+    hasher.finalize(&mut hash);
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UInt256;
+
+    #[test]
+    fn hashes_empty_input() {
+        assert_eq!(
+            UInt256::from(keccak256(&[])),
+            UInt256::from_string(
+                "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+            )
+        );
+    }
+}
@@ -0,0 +1,20 @@
+pub mod abi;
+pub mod address;
+pub mod env;
+pub mod instructions;
+pub mod keccak;
+pub mod remix_json;
+pub mod rlp;
+pub mod state;
+pub mod storage;
+pub mod uint256;
+pub mod units;
+pub mod vm;
+
+pub use crate::abi::Message;
+pub use crate::address::Address;
+pub use crate::env::{BlockEnv, Env, TxEnv};
+pub use crate::keccak::keccak256;
+pub use crate::state::{Log, WorldState};
+pub use crate::storage::{FileBackend, MemoryBackend, StateBackend, StorageError};
+pub use crate::uint256::UInt256;
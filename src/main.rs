@@ -1,29 +1,14 @@
-use std::fs;
+use std::env;
 
-mod abi;
-mod instructions;
-mod remix_json;
-mod storage;
-mod uint256;
-mod vm;
-
-use crate::abi::*;
-use crate::remix_json::read_remix_json;
-use crate::uint256::UInt256;
-use crate::vm::*;
-
-impl InputManager {
-    fn from_file(filename: &str) -> InputManager {
-        let contents = fs::read_to_string(filename).expect("Something went wrong reading the file");
-        InputManager::from_string(&contents)
-    }
-}
+use rusty_ethereum::abi::BLOCK_GAS_LIMIT;
+use rusty_ethereum::remix_json::read_remix_json;
+use rusty_ethereum::state::create_address;
+use rusty_ethereum::uint256::hex_string_as_vec_u8;
+use rusty_ethereum::vm::*;
+use rusty_ethereum::{Address, Env, FileBackend, Message, WorldState};
 
 #[allow(dead_code)]
 fn main_disassemble() {
-    // let filename = "bin/fixtures/Counter.bin";
-    // let mut input = InputManager::from_file(filename);
-
     let filename = "fixtures/counter_bytecode_8_0_1_remix.json";
     let result = read_remix_json(filename);
     let mut input = InputManager::from_string(&result.object);
@@ -37,12 +22,44 @@ fn main_disassemble() {
 #[allow(dead_code)]
 fn main() {
     // main_disassemble();
-    let method_names = vec!["get()", "count()", "inc()", "dec()"];
-    let message = Message::new_call(method_names[1]);
-    let filename = "bin/fixtures/Counter.bin";
-    let contract = InputManager::from_file(&filename);
-    match send_message_to_contract(message, contract) {
-        Ok(()) => println!("DONE!"),
+    let method_names = ["get()", "count()", "inc()", "dec()"];
+    let filename = "fixtures/counter_bytecode_8_0_1_remix.json";
+    let env = Env::default();
+    // Accounts persist between runs in the file named by the first argument.
+    let path = env::args()
+        .nth(1)
+        .unwrap_or_else(|| String::from("state.json"));
+    let mut backend = FileBackend::new(path);
+    let mut state = match WorldState::load(&backend) {
+        Ok(state) => state,
+        Err(error) => return println!("ERROR: {:?}", error),
+    };
+    // The counter is the first contract the zero address deploys, so a
+    // state saved by an earlier run already has it.
+    let counter = create_address(Address::ZERO, 0);
+    let address = if state.code(counter).is_empty() {
+        let deployment = Message {
+            gas_limit: BLOCK_GAS_LIMIT,
+            data: hex_string_as_vec_u8(&read_remix_json(filename).object),
+            ..Message::default()
+        };
+        match create_contract(&env, &mut state, deployment) {
+            Ok(result) => result.contract_address.expect("creates return an address"),
+            Err(error) => return println!("ERROR: {:?}", error),
+        }
+    } else {
+        println!("Using the counter deployed at {}", counter);
+        counter
+    };
+    let message = Message {
+        address,
+        ..Message::new_call(method_names[1])
+    };
+    match send_message_to_contract(&env, &mut state, message) {
+        Ok(result) => println!("DONE! (gas used: {})", result.gas_used),
         Err(error) => println!("ERROR: {:?}", error),
     }
+    if let Err(error) = state.save(&mut backend) {
+        println!("ERROR: {:?}", error);
+    }
 }
@@ -1,7 +1,6 @@
 use std::fs;
 
 use serde::Deserialize;
-use serde_json;
 
 #[derive(Deserialize)]
 pub struct RemixCompileResult {
@@ -11,5 +10,5 @@ pub struct RemixCompileResult {
 
 pub fn read_remix_json(filename: &str) -> RemixCompileResult {
     let contents = fs::read_to_string(filename).expect("Something went wrong reading the file");
-    return serde_json::from_str(&contents).unwrap();
+    serde_json::from_str(&contents).unwrap()
 }
@@ -0,0 +1,60 @@
+// Recursive Length Prefix encoding (Yellow Paper appendix B), as much of
+// it as deriving contract addresses needs.
+
+// A short item's length is added to `offset`; a long one's length follows
+// the prefix as big-endian bytes, and their count is added instead.
+fn length_prefix(offset: u8, length: usize) -> Vec<u8> {
+    if length < 56 {
+        return vec![offset + length as u8];
+    }
+    let bytes = length.to_be_bytes();
+    let skip = bytes.iter().take_while(|&&byte| byte == 0).count();
+    let mut prefix = vec![offset + 55 + (bytes.len() - skip) as u8];
+    prefix.extend_from_slice(&bytes[skip..]);
+    prefix
+}
+
+pub fn encode_bytes(bytes: &[u8]) -> Vec<u8> {
+    // A single byte below 0x80 is its own encoding.
+    if let [byte @ 0..=0x7F] = bytes {
+        return vec![*byte];
+    }
+    let mut encoded = length_prefix(0x80, bytes.len());
+    encoded.extend_from_slice(bytes);
+    encoded
+}
+
+// Integers are encoded as their big-endian bytes without leading zeros,
+// so zero is the empty string.
+pub fn encode_u64(value: u64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let skip = bytes.iter().take_while(|&&byte| byte == 0).count();
+    encode_bytes(&bytes[skip..])
+}
+
+// Takes items that are already encoded.
+pub fn encode_list(items: &[Vec<u8>]) -> Vec<u8> {
+    let payload = items.concat();
+    let mut encoded = length_prefix(0xC0, payload.len());
+    encoded.extend(payload);
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_strings_integers_and_lists() {
+        assert_eq!(encode_u64(0), vec![0x80]);
+        assert_eq!(encode_u64(0x7F), vec![0x7F]);
+        assert_eq!(encode_u64(0x400), vec![0x82, 0x04, 0x00]);
+        assert_eq!(encode_bytes(b"dog"), vec![0x83, b'd', b'o', b'g']);
+        let long = [0xAAu8; 56];
+        assert_eq!(encode_bytes(&long)[..2], [0xB8, 56]);
+        assert_eq!(
+            encode_list(&[encode_bytes(b"cat"), encode_bytes(b"dog")]),
+            vec![0xC8, 0x83, b'c', b'a', b't', 0x83, b'd', b'o', b'g']
+        );
+    }
+}
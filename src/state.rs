@@ -0,0 +1,605 @@
+use std::collections::{HashMap, HashSet};
+use std::mem;
+
+use crate::rlp::{encode_bytes, encode_list, encode_u64};
+use crate::storage::{StateBackend, StorageError};
+use crate::{keccak256, Address, UInt256};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Account {
+    pub balance: UInt256, // in wei
+    // Transactions sent, or for a contract, contracts created.
+    pub nonce: u64,
+    pub code: Vec<u8>,
+    // keccak256 of `code`, which WorldState keeps in step with it.
+    pub code_hash: UInt256,
+    pub storage: HashMap<UInt256, UInt256>,
+}
+
+impl Default for Account {
+    fn default() -> Self {
+        Account {
+            balance: UInt256::ZERO,
+            nonce: 0,
+            code: Vec::new(),
+            code_hash: UInt256::from(keccak256(&[])),
+            storage: HashMap::new(),
+        }
+    }
+}
+
+/// The address CREATE deploys to: the hash of the RLP list
+/// [sender, nonce].
+pub fn create_address(sender: Address, nonce: u64) -> Address {
+    let encoded = encode_list(&[encode_bytes(&sender.0), encode_u64(nonce)]);
+    Address::from_be_slice(&keccak256(&encoded)[12..])
+}
+
+/// The address CREATE2 deploys to (EIP-1014), which depends on the init
+/// code instead of the sender's nonce.
+pub fn create2_address(sender: Address, salt: UInt256, init_code: &[u8]) -> Address {
+    let mut preimage = vec![0xFF];
+    preimage.extend_from_slice(&sender.0);
+    preimage.extend_from_slice(&<[u8; 32]>::from(salt));
+    preimage.extend_from_slice(&keccak256(init_code));
+    Address::from_be_slice(&keccak256(&preimage)[12..])
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Log {
+    pub address: Address,
+    pub topics: Vec<UInt256>,
+    pub data: Vec<u8>,
+}
+
+// One change to the world state, holding what it overwrote so that it can
+// be undone.
+#[derive(Clone, Debug)]
+enum JournalEntry {
+    AccountAdded {
+        address: Address,
+    },
+    BalanceChanged {
+        address: Address,
+        previous: UInt256,
+    },
+    NonceChanged {
+        address: Address,
+        previous: u64,
+    },
+    CodeChanged {
+        address: Address,
+        previous: Vec<u8>,
+        previous_hash: UInt256,
+    },
+    StorageChanged {
+        address: Address,
+        key: UInt256,
+        previous: UInt256,
+    },
+    TransientStorageChanged {
+        address: Address,
+        key: UInt256,
+        previous: UInt256,
+    },
+    ContractCreated {
+        address: Address,
+    },
+    SelfDestructed {
+        address: Address,
+    },
+    LogAdded,
+    AccountWarmed {
+        address: Address,
+    },
+    StorageWarmed {
+        address: Address,
+        key: UInt256,
+    },
+    RefundChanged {
+        previous: u64,
+    },
+}
+
+/// Marks a point to roll the world state back to, from `WorldState::checkpoint`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Checkpoint {
+    journal_length: usize,
+}
+
+/// The accounts every call frame of a transaction reads and writes.
+/// Addresses that were never touched read as empty accounts.
+///
+/// Every change made while a checkpoint is open is journaled, so each call
+/// can take a checkpoint when it starts and either commit or revert it
+/// when it ends.
+#[derive(Clone, Debug, Default)]
+pub struct WorldState {
+    accounts: HashMap<Address, Account>,
+    // EIP-1153 storage, discarded at the end of every transaction.
+    transient_storage: HashMap<(Address, UInt256), UInt256>,
+    logs: Vec<Log>,
+    // Contracts created by the current transaction, and those of them
+    // that have self-destructed.
+    created: HashSet<Address>,
+    destructed: HashSet<Address>,
+    // EIP-2929: the accounts and slots touched so far, which are cheaper
+    // to touch again.
+    warm_accounts: HashSet<Address>,
+    warm_storage: HashSet<(Address, UInt256)>,
+    // Slots as they were before the transaction first wrote them, which
+    // EIP-2200 prices writes against.
+    original_storage: HashMap<(Address, UInt256), UInt256>,
+    // Gas given back once the transaction succeeds, for clearing storage.
+    refund: u64,
+    journal: Vec<JournalEntry>,
+    open_checkpoints: usize,
+}
+
+impl WorldState {
+    /// The accounts last saved to `backend`.
+    pub fn load(backend: &dyn StateBackend) -> Result<WorldState, StorageError> {
+        Ok(WorldState {
+            accounts: backend.load()?,
+            ..WorldState::default()
+        })
+    }
+
+    /// Saves every account to `backend`. Meant for between transactions:
+    /// nothing else the state holds outlives one.
+    pub fn save(&self, backend: &mut dyn StateBackend) -> Result<(), StorageError> {
+        backend.save(&self.accounts)
+    }
+
+    pub fn account(&self, address: Address) -> Option<&Account> {
+        self.accounts.get(&address)
+    }
+
+    /// An account with no balance, nonce or code, as defined by EIP-161.
+    pub fn is_empty(&self, address: Address) -> bool {
+        self.account(address).is_none_or(|account| {
+            account.balance == UInt256::ZERO && account.nonce == 0 && account.code.is_empty()
+        })
+    }
+
+    pub fn balance(&self, address: Address) -> UInt256 {
+        self.account(address)
+            .map_or(UInt256::ZERO, |account| account.balance)
+    }
+
+    pub fn nonce(&self, address: Address) -> u64 {
+        self.account(address).map_or(0, |account| account.nonce)
+    }
+
+    pub fn code(&self, address: Address) -> &[u8] {
+        self.account(address)
+            .map_or(&[], |account| account.code.as_slice())
+    }
+
+    /// EXTCODEHASH's view of an account: zero if it is empty, otherwise
+    /// the hash of its code, even when that code is empty.
+    pub fn code_hash(&self, address: Address) -> UInt256 {
+        match self.account(address) {
+            Some(account) if !self.is_empty(address) => account.code_hash,
+            _ => UInt256::ZERO,
+        }
+    }
+
+    pub fn storage(&self, address: Address, key: UInt256) -> UInt256 {
+        self.account(address)
+            .and_then(|account| account.storage.get(&key))
+            .copied()
+            .unwrap_or(UInt256::ZERO)
+    }
+
+    /// What `key` held when the transaction started.
+    pub fn original_storage(&self, address: Address, key: UInt256) -> UInt256 {
+        self.original_storage
+            .get(&(address, key))
+            .copied()
+            .unwrap_or_else(|| self.storage(address, key))
+    }
+
+    pub fn refund(&self) -> u64 {
+        self.refund
+    }
+
+    pub fn transient_storage(&self, address: Address, key: UInt256) -> UInt256 {
+        self.transient_storage
+            .get(&(address, key))
+            .copied()
+            .unwrap_or(UInt256::ZERO)
+    }
+
+    // Changes made outside any checkpoint can never be reverted, so they
+    // aren't worth remembering.
+    fn record(&mut self, entry: JournalEntry) {
+        if self.open_checkpoints > 0 {
+            self.journal.push(entry);
+        }
+    }
+
+    fn account_mut(&mut self, address: Address) -> &mut Account {
+        if !self.accounts.contains_key(&address) {
+            self.record(JournalEntry::AccountAdded { address });
+        }
+        self.accounts.entry(address).or_default()
+    }
+
+    pub fn set_balance(&mut self, address: Address, balance: UInt256) {
+        let previous = mem::replace(&mut self.account_mut(address).balance, balance);
+        self.record(JournalEntry::BalanceChanged { address, previous });
+    }
+
+    pub fn increment_nonce(&mut self, address: Address) {
+        let account = self.account_mut(address);
+        let previous = account.nonce;
+        account.nonce += 1;
+        self.record(JournalEntry::NonceChanged { address, previous });
+    }
+
+    pub fn set_code(&mut self, address: Address, code: Vec<u8>) {
+        let code_hash = UInt256::from(keccak256(&code));
+        let account = self.account_mut(address);
+        let previous_hash = mem::replace(&mut account.code_hash, code_hash);
+        let previous = mem::replace(&mut account.code, code);
+        self.record(JournalEntry::CodeChanged {
+            address,
+            previous,
+            previous_hash,
+        });
+    }
+
+    // Zero is every slot's default, so storing it frees the slot.
+    pub fn set_storage(&mut self, address: Address, key: UInt256, value: UInt256) {
+        let storage = &mut self.account_mut(address).storage;
+        let previous = if value == UInt256::ZERO {
+            storage.remove(&key)
+        } else {
+            storage.insert(key, value)
+        }
+        .unwrap_or(UInt256::ZERO);
+        // Only writes made during a transaction have an original to
+        // remember. Reverting one restores it, so it isn't journaled.
+        if self.open_checkpoints > 0 {
+            self.original_storage
+                .entry((address, key))
+                .or_insert(previous);
+        }
+        self.record(JournalEntry::StorageChanged {
+            address,
+            key,
+            previous,
+        });
+    }
+
+    pub fn set_transient_storage(&mut self, address: Address, key: UInt256, value: UInt256) {
+        let previous = self.transient_storage(address, key);
+        self.record(JournalEntry::TransientStorageChanged {
+            address,
+            key,
+            previous,
+        });
+        self.transient_storage.insert((address, key), value);
+    }
+
+    /// Marks `address` as accessed, returning whether it was cold.
+    pub fn warm_account(&mut self, address: Address) -> bool {
+        let is_cold = self.warm_accounts.insert(address);
+        if is_cold {
+            self.record(JournalEntry::AccountWarmed { address });
+        }
+        is_cold
+    }
+
+    /// Marks a storage slot as accessed, returning whether it was cold.
+    pub fn warm_storage(&mut self, address: Address, key: UInt256) -> bool {
+        let is_cold = self.warm_storage.insert((address, key));
+        if is_cold {
+            self.record(JournalEntry::StorageWarmed { address, key });
+        }
+        is_cold
+    }
+
+    pub fn add_refund(&mut self, amount: u64) {
+        self.record(JournalEntry::RefundChanged {
+            previous: self.refund,
+        });
+        self.refund += amount;
+    }
+
+    pub fn remove_refund(&mut self, amount: u64) {
+        self.record(JournalEntry::RefundChanged {
+            previous: self.refund,
+        });
+        self.refund -= amount;
+    }
+
+    pub fn add_log(&mut self, log: Log) {
+        self.record(JournalEntry::LogAdded);
+        self.logs.push(log);
+    }
+
+    /// Moves `value` wei between two accounts. Returns false, changing
+    /// nothing, if `from` can't cover it.
+    pub fn transfer(&mut self, from: Address, to: Address, value: UInt256) -> bool {
+        let from_balance = self.balance(from);
+        if from_balance < value {
+            return false;
+        }
+        self.set_balance(from, from_balance - value);
+        let to_balance = self.balance(to);
+        self.set_balance(to, to_balance + value);
+        true
+    }
+
+    /// Turns `address` into a new contract. EIP-161 starts contracts at a
+    /// nonce of one; any balance already sent there is kept.
+    pub fn create_contract(&mut self, address: Address) {
+        self.increment_nonce(address);
+        if self.created.insert(address) {
+            self.record(JournalEntry::ContractCreated { address });
+        }
+    }
+
+    /// SELFDESTRUCT sends the whole balance to `beneficiary`. Since
+    /// EIP-6780 the account itself is only deleted, at the end of the
+    /// transaction, if that same transaction created it.
+    pub fn self_destruct(&mut self, address: Address, beneficiary: Address) {
+        let balance = self.balance(address);
+        self.transfer(address, beneficiary, balance);
+        if self.created.contains(&address) {
+            // Burns the balance when the beneficiary is the account itself.
+            self.set_balance(address, UInt256::ZERO);
+            if self.destructed.insert(address) {
+                self.record(JournalEntry::SelfDestructed { address });
+            }
+        }
+    }
+
+    pub fn checkpoint(&mut self) -> Checkpoint {
+        self.open_checkpoints += 1;
+        Checkpoint {
+            journal_length: self.journal.len(),
+        }
+    }
+
+    /// Keeps the changes made since the innermost open checkpoint. They
+    /// stay journaled until the outermost one closes, since an enclosing
+    /// checkpoint may still revert them.
+    pub fn commit(&mut self) {
+        self.open_checkpoints -= 1;
+        if self.open_checkpoints == 0 {
+            self.journal.clear();
+        }
+    }
+
+    /// Undoes every change made since `checkpoint`, which must be the
+    /// innermost open one.
+    pub fn revert(&mut self, checkpoint: Checkpoint) {
+        while self.journal.len() > checkpoint.journal_length {
+            let entry = self
+                .journal
+                .pop()
+                .expect("journal is longer than checkpoint");
+            self.undo(entry);
+        }
+        self.open_checkpoints -= 1;
+    }
+
+    fn undo(&mut self, entry: JournalEntry) {
+        // Every change is journaled after the account it touches was
+        // added, so undoing newest first always finds the account.
+        fn account(accounts: &mut HashMap<Address, Account>, address: Address) -> &mut Account {
+            accounts.get_mut(&address).expect("journaled account")
+        }
+        let accounts = &mut self.accounts;
+        match entry {
+            JournalEntry::AccountAdded { address } => {
+                accounts.remove(&address);
+            }
+            JournalEntry::BalanceChanged { address, previous } => {
+                account(accounts, address).balance = previous;
+            }
+            JournalEntry::NonceChanged { address, previous } => {
+                account(accounts, address).nonce = previous;
+            }
+            JournalEntry::CodeChanged {
+                address,
+                previous,
+                previous_hash,
+            } => {
+                let account = account(accounts, address);
+                account.code = previous;
+                account.code_hash = previous_hash;
+            }
+            JournalEntry::StorageChanged {
+                address,
+                key,
+                previous,
+            } => {
+                let storage = &mut account(accounts, address).storage;
+                if previous == UInt256::ZERO {
+                    storage.remove(&key);
+                } else {
+                    storage.insert(key, previous);
+                }
+            }
+            JournalEntry::TransientStorageChanged {
+                address,
+                key,
+                previous,
+            } => {
+                self.transient_storage.insert((address, key), previous);
+            }
+            JournalEntry::ContractCreated { address } => {
+                self.created.remove(&address);
+            }
+            JournalEntry::SelfDestructed { address } => {
+                self.destructed.remove(&address);
+            }
+            JournalEntry::LogAdded => {
+                self.logs.pop();
+            }
+            JournalEntry::AccountWarmed { address } => {
+                self.warm_accounts.remove(&address);
+            }
+            JournalEntry::StorageWarmed { address, key } => {
+                self.warm_storage.remove(&(address, key));
+            }
+            JournalEntry::RefundChanged { previous } => {
+                self.refund = previous;
+            }
+        }
+    }
+
+    /// Ends the transaction: deletes self-destructed contracts, forgets
+    /// transient storage, access and refunds, and hands back the logs
+    /// emitted.
+    pub fn finish_transaction(&mut self) -> Vec<Log> {
+        for address in self.destructed.drain() {
+            self.accounts.remove(&address);
+        }
+        self.created.clear();
+        self.transient_storage.clear();
+        self.warm_accounts.clear();
+        self.warm_storage.clear();
+        self.original_storage.clear();
+        self.refund = 0;
+        self.journal.clear();
+        self.open_checkpoints = 0;
+        mem::take(&mut self.logs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryBackend;
+
+    fn address(hex: &str) -> Address {
+        Address::from(UInt256::from_string(hex))
+    }
+
+    #[test]
+    fn transfer_moves_value_or_nothing() {
+        let (alice, bob) = (Address([0xAA; 20]), Address([0xBB; 20]));
+        let mut state = WorldState::default();
+        state.set_balance(alice, UInt256::from(10u8));
+        assert!(state.transfer(alice, bob, UInt256::from(4u8)));
+        assert_eq!(state.balance(alice), UInt256::from(6u8));
+        assert_eq!(state.balance(bob), UInt256::from(4u8));
+        assert!(!state.transfer(alice, bob, UInt256::from(7u8)));
+        assert_eq!(state.balance(alice), UInt256::from(6u8));
+        // Sending to yourself leaves the balance unchanged.
+        assert!(state.transfer(bob, bob, UInt256::from(4u8)));
+        assert_eq!(state.balance(bob), UInt256::from(4u8));
+        assert!(!state.is_empty(bob));
+        assert!(state.is_empty(Address::ZERO));
+    }
+    #[test]
+    fn derives_contract_addresses() {
+        let sender = address("6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0");
+        assert_eq!(
+            create_address(sender, 0),
+            address("cd234a471b72ba2f1ccf0a70fcaba648a5eecd8d")
+        );
+        assert_eq!(
+            create_address(sender, 1),
+            address("343c43a37d37dff08ae8c4a11544c718abb4fcf8")
+        );
+        // The examples from EIP-1014.
+        assert_eq!(
+            create2_address(Address::ZERO, UInt256::ZERO, &[0x00]),
+            address("4d1a2e2bb4f88f0250f26ffff098b0b30b26bf38")
+        );
+        let sender = address("deadbeef00000000000000000000000000000000");
+        assert_eq!(
+            create2_address(sender, UInt256::ZERO, &[0x00]),
+            address("b928f69bb1d91cd65274e3c79d8986362984fda3")
+        );
+    }
+    #[test]
+    fn code_hash_and_storage_are_per_account() {
+        let (alice, bob) = (Address([0xAA; 20]), Address([0xBB; 20]));
+        let mut state = WorldState::default();
+        state.set_storage(alice, UInt256::ONE, UInt256::from(5u8));
+        assert_eq!(state.storage(alice, UInt256::ONE), UInt256::from(5u8));
+        assert_eq!(state.storage(bob, UInt256::ONE), UInt256::ZERO);
+        state.set_storage(alice, UInt256::ONE, UInt256::ZERO);
+        assert!(state.account(alice).expect("ok").storage.is_empty());
+        // Empty accounts hash to zero, others to the hash of their code.
+        assert_eq!(state.code_hash(bob), UInt256::ZERO);
+        state.set_balance(bob, UInt256::ONE);
+        assert_eq!(state.code_hash(bob), UInt256::from(keccak256(&[])));
+        state.set_code(bob, vec![0x00]);
+        assert_eq!(state.code_hash(bob), UInt256::from(keccak256(&[0x00])));
+    }
+    #[test]
+    fn revert_undoes_everything_since_its_checkpoint() {
+        let (alice, bob) = (Address([0xAA; 20]), Address([0xBB; 20]));
+        let key = UInt256::from(7u8);
+        let mut state = WorldState::default();
+        state.set_balance(alice, UInt256::from(10u8));
+        state.set_storage(alice, key, UInt256::ONE);
+
+        let outer = state.checkpoint();
+        state.set_storage(alice, key, UInt256::from(2u8));
+        // A committed inner checkpoint is still undone with the outer one.
+        state.checkpoint();
+        assert!(state.transfer(alice, bob, UInt256::from(4u8)));
+        state.increment_nonce(alice);
+        state.set_code(bob, vec![0x00]);
+        state.set_transient_storage(bob, key, UInt256::ONE);
+        assert!(state.warm_account(bob));
+        state.add_refund(4800);
+        state.add_log(Log {
+            address: bob,
+            topics: Vec::new(),
+            data: Vec::new(),
+        });
+        state.commit();
+        state.revert(outer);
+
+        assert_eq!(state.balance(alice), UInt256::from(10u8));
+        assert_eq!(state.nonce(alice), 0);
+        assert_eq!(state.storage(alice, key), UInt256::ONE);
+        assert!(state.account(bob).is_none());
+        assert_eq!(state.transient_storage(bob, key), UInt256::ZERO);
+        assert!(state.warm_account(bob));
+        assert_eq!(state.refund(), 0);
+        assert!(state.finish_transaction().is_empty());
+    }
+    #[test]
+    fn only_contracts_created_this_transaction_are_deleted() {
+        let (old, new, heir) = (
+            Address([0x01; 20]),
+            Address([0x02; 20]),
+            Address([0x03; 20]),
+        );
+        let mut state = WorldState::default();
+        state.create_contract(old);
+        state.finish_transaction();
+        state.create_contract(new);
+        for contract in [old, new].iter() {
+            state.set_code(*contract, vec![0x00]);
+            state.set_balance(*contract, UInt256::from(5u8));
+            state.self_destruct(*contract, heir);
+        }
+        state.finish_transaction();
+        assert_eq!(state.code(old), [0x00]);
+        assert_eq!(state.balance(old), UInt256::ZERO);
+        assert!(state.account(new).is_none());
+        assert_eq!(state.balance(heir), UInt256::from(10u8));
+    }
+    #[test]
+    fn loads_what_it_saved() {
+        let mut backend = MemoryBackend::default();
+        let mut state = WorldState::default();
+        state.set_balance(Address([0xAA; 20]), UInt256::from(10u8));
+        state.set_storage(Address([0xBB; 20]), UInt256::ONE, UInt256::ONE);
+        state.save(&mut backend).expect("ok");
+        let loaded = WorldState::load(&backend).expect("ok");
+        assert_eq!(loaded.accounts, state.accounts);
+    }
+}
@@ -1,67 +1,219 @@
 use serde::{Deserialize, Serialize};
-use serde_json;
 use std::collections::HashMap;
 use std::fmt;
 use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
 
-use crate::UInt256;
+use crate::state::Account;
+use crate::{keccak256, Address, UInt256};
 
-#[derive(Default)]
-pub struct Storage {
-    // address: UInt256,
-}
-
-#[derive(Serialize, Deserialize, Default)]
-pub struct StorageFile {
-    key_pairs: HashMap<String, String>,
+/// Somewhere to keep the world state between transactions.
+pub trait StateBackend {
+    /// Every account saved so far; none if nothing has been saved yet.
+    fn load(&self) -> Result<HashMap<Address, Account>, StorageError>;
+    /// Replaces everything saved with `accounts`.
+    fn save(&mut self, accounts: &HashMap<Address, Account>) -> Result<(), StorageError>;
 }
 
 #[derive(Copy, Clone, PartialEq, Eq)]
 pub enum StorageError {
+    CantRead(io::ErrorKind),
+    CantParse { line: usize, column: usize },
+    BadCode(Address),
     CantSerialize,
+    CantWrite(io::ErrorKind),
 }
 
 impl fmt::Debug for StorageError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+            StorageError::CantRead(kind) => write!(f, "CantRead({:?})", kind),
+            StorageError::CantParse { line, column } => {
+                write!(f, "CantParse at {}:{}", line, column)
+            }
+            StorageError::BadCode(address) => write!(f, "BadCode({})", address),
             StorageError::CantSerialize => write!(f, "CantSerialize"),
+            StorageError::CantWrite(kind) => write!(f, "CantWrite({:?})", kind),
         }
     }
 }
 
-impl Storage {
-    fn filename(&self) -> String {
-        String::from("storage.txt")
+/// Keeps accounts in memory, for tests and throwaway runs.
+#[derive(Default)]
+pub struct MemoryBackend {
+    accounts: HashMap<Address, Account>,
+}
+
+impl StateBackend for MemoryBackend {
+    fn load(&self) -> Result<HashMap<Address, Account>, StorageError> {
+        Ok(self.accounts.clone())
     }
 
-    pub fn load_storage_file(&self) -> StorageFile {
-        match fs::read_to_string(self.filename()) {
-            Ok(contents) => serde_json::from_str(&contents).expect("parse failure"),
-            Err(e) => match e.kind() {
-                // Return Err for non-recoverable errors?
-                _ => StorageFile::default(),
-            },
-        }
+    fn save(&mut self, accounts: &HashMap<Address, Account>) -> Result<(), StorageError> {
+        self.accounts = accounts.clone();
+        Ok(())
+    }
+}
+
+// How an account is written to disk. Code is kept as hex, and its hash is
+// recomputed on load rather than trusted.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct AccountEntry {
+    balance: UInt256,
+    nonce: u64,
+    code: String,
+    storage: HashMap<UInt256, UInt256>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct StateFile {
+    accounts: HashMap<Address, AccountEntry>,
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    let digits: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("0x{}", digits)
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Keeps accounts in a JSON file at `path`. The file is read once per load
+/// and written once per save, by writing a temporary file next to it and
+/// renaming that over it, so a failed save leaves the old file intact.
+pub struct FileBackend {
+    path: PathBuf,
+}
+
+impl FileBackend {
+    pub fn new(path: impl Into<PathBuf>) -> FileBackend {
+        FileBackend { path: path.into() }
+    }
+
+    fn temporary_path(&self) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(".tmp");
+        PathBuf::from(path)
     }
+}
 
-    pub fn load(&self, key: UInt256) -> Result<UInt256, StorageError> {
-        let storage_file = self.load_storage_file();
-        let key_string = format!("{}", key);
-        match storage_file.key_pairs.get(&key_string) {
-            Some(value_str) => Ok(UInt256::from_string(&value_str)),
-            None => Ok(UInt256::ZERO),
+impl StateBackend for FileBackend {
+    fn load(&self) -> Result<HashMap<Address, Account>, StorageError> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(HashMap::new()),
+            Err(error) => return Err(StorageError::CantRead(error.kind())),
+        };
+        let state_file: StateFile =
+            serde_json::from_str(&contents).map_err(|error| StorageError::CantParse {
+                line: error.line(),
+                column: error.column(),
+            })?;
+        let mut accounts = HashMap::new();
+        for (address, entry) in state_file.accounts {
+            let code = decode_hex(&entry.code).ok_or(StorageError::BadCode(address))?;
+            let account = Account {
+                balance: entry.balance,
+                nonce: entry.nonce,
+                code_hash: UInt256::from(keccak256(&code)),
+                code,
+                storage: entry.storage,
+            };
+            accounts.insert(address, account);
         }
+        Ok(accounts)
     }
 
-    pub fn store(&mut self, key: UInt256, value: UInt256) -> Result<(), StorageError> {
-        let mut storage_file = self.load_storage_file();
-        let key_string = format!("{}", key);
-        let value_string = format!("{}", value);
-        storage_file.key_pairs.insert(key_string, value_string);
+    fn save(&mut self, accounts: &HashMap<Address, Account>) -> Result<(), StorageError> {
+        let state_file = StateFile {
+            accounts: accounts
+                .iter()
+                .map(|(address, account)| {
+                    let entry = AccountEntry {
+                        balance: account.balance,
+                        nonce: account.nonce,
+                        code: encode_hex(&account.code),
+                        storage: account.storage.clone(),
+                    };
+                    (*address, entry)
+                })
+                .collect(),
+        };
+        let contents =
+            serde_json::to_string(&state_file).map_err(|_| StorageError::CantSerialize)?;
+        let temporary_path = self.temporary_path();
+        let write = || -> io::Result<()> {
+            let mut file = fs::File::create(&temporary_path)?;
+            file.write_all(contents.as_bytes())?;
+            file.sync_all()?;
+            fs::rename(&temporary_path, &self.path)
+        };
+        write().map_err(|error| {
+            let _ = fs::remove_file(&temporary_path);
+            StorageError::CantWrite(error.kind())
+        })
+    }
+}
 
-        let write_contents: String =
-            serde_json::to_string(&storage_file).map_err(|_| StorageError::CantSerialize)?;
-        fs::write(self.filename(), write_contents).expect("write fail");
-        Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contract() -> (Address, Account) {
+        let mut account = Account {
+            balance: UInt256::from(5u8),
+            nonce: 1,
+            code: vec![0x60, 0x00, 0xFF],
+            ..Account::default()
+        };
+        account.code_hash = UInt256::from(keccak256(&account.code));
+        account.storage.insert(UInt256::ZERO, UInt256::from(3u8));
+        (Address([0xCC; 20]), account)
+    }
+
+    #[test]
+    fn state_file_reads_hex_keys() {
+        let state_file: StateFile = serde_json::from_str(
+            r#"{"accounts":{"0x01":{"code":"0x00","storage":{"0x00":"0x03"}}}}"#,
+        )
+        .expect("parses");
+        let entry = &state_file.accounts[&Address::from(UInt256::ONE)];
+        assert_eq!(entry.balance, UInt256::ZERO);
+        assert_eq!(decode_hex(&entry.code), Some(vec![0x00]));
+        assert_eq!(entry.storage.get(&UInt256::ZERO), Some(&UInt256::from(3u8)));
+        assert_eq!(decode_hex("0x0"), None);
+        assert_eq!(decode_hex("0xzz"), None);
+    }
+    #[test]
+    fn backends_give_back_what_they_saved() {
+        let path = std::env::temp_dir().join(format!("state-{}.json", std::process::id()));
+        let accounts: HashMap<_, _> = [contract()].iter().cloned().collect();
+        let mut memory = MemoryBackend::default();
+        let mut file = FileBackend::new(&path);
+        for backend in [&mut memory as &mut dyn StateBackend, &mut file].iter_mut() {
+            assert!(backend.load().expect("ok").is_empty());
+            backend.save(&accounts).expect("ok");
+            assert_eq!(backend.load().expect("ok"), accounts);
+        }
+        assert!(!file.temporary_path().exists());
+        fs::write(&path, "{\"accounts\": 1}").expect("ok");
+        assert_eq!(
+            file.load(),
+            Err(StorageError::CantParse {
+                line: 1,
+                column: 14
+            })
+        );
+        fs::remove_file(&path).expect("ok");
     }
 }
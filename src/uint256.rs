@@ -1,19 +1,23 @@
 use itertools::Itertools;
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
 use std::convert::{TryFrom, TryInto};
 use std::fmt;
 use std::iter::Iterator;
-use std::ops::{Add, Not, Shl, Shr, Sub};
+use std::ops::{Add, BitAnd, BitOr, BitXor, Div, Mul, Not, Rem, Shl, Shr, Sub};
+use std::str::FromStr;
 
-#[derive(Default, Copy, Clone, PartialEq, PartialOrd, Eq)]
+/// A 256-bit EVM word stored as four u64 limbs, least significant first.
+#[derive(Default, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct UInt256 {
-    high: u128,
-    low: u128,
+    limbs: [u64; 4],
 }
 
 pub fn hex_string_as_vec_u8(hex: &str) -> Vec<u8> {
     // NOTE: This does allow 0x0x which might be bad?
-    if hex.starts_with("0x") {
-        return hex_string_as_vec_u8(&hex[2..]);
+    if let Some(stripped) = hex.strip_prefix("0x") {
+        return hex_string_as_vec_u8(stripped);
     }
     let chars = hex.chars();
     let chunks = chars.chunks(2);
@@ -23,9 +27,144 @@ pub fn hex_string_as_vec_u8(hex: &str) -> Vec<u8> {
         .collect::<Vec<u8>>()
 }
 
+// a + b + carry, returning the carry out. On x86_64 this is the ADC
+// instruction; elsewhere LLVM recognizes the overflowing_add pair.
+#[inline(always)]
+fn add_with_carry(a: u64, b: u64, carry: bool) -> (u64, bool) {
+    #[cfg(target_arch = "x86_64")]
+    {
+        let mut sum = 0u64;
+        let carry = std::arch::x86_64::_addcarry_u64(carry as u8, a, b, &mut sum);
+        (sum, carry != 0)
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        let (sum, carry_a) = a.overflowing_add(b);
+        let (sum, carry_b) = sum.overflowing_add(carry as u64);
+        (sum, carry_a || carry_b)
+    }
+}
+
+// a - b - borrow, returning the borrow out (SBB on x86_64).
+#[inline(always)]
+fn sub_with_borrow(a: u64, b: u64, borrow: bool) -> (u64, bool) {
+    #[cfg(target_arch = "x86_64")]
+    {
+        let mut difference = 0u64;
+        let borrow = std::arch::x86_64::_subborrow_u64(borrow as u8, a, b, &mut difference);
+        (difference, borrow != 0)
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        let (difference, borrow_a) = a.overflowing_sub(b);
+        let (difference, borrow_b) = difference.overflowing_sub(borrow as u64);
+        (difference, borrow_a || borrow_b)
+    }
+}
+
+// Number of limbs up to and including the most significant non-zero one.
+fn significant_limbs(limbs: &[u64]) -> usize {
+    limbs
+        .iter()
+        .rposition(|&limb| limb != 0)
+        .map_or(0, |i| i + 1)
+}
+
+// Long division of `limbs` in place by a single limb, returning the
+// remainder.
+fn div_rem_limb(limbs: &mut [u64], divisor: u64) -> u64 {
+    let mut remainder: u128 = 0;
+    for limb in limbs.iter_mut().rev() {
+        let current = (remainder << 64) | *limb as u128;
+        *limb = (current / divisor as u128) as u64;
+        remainder = current % divisor as u128;
+    }
+    remainder as u64
+}
+
+// Knuth's Algorithm D (TAOCP 4.3.1), following the layout of Hacker's
+// Delight `divmnu`. `divisor` must have at least two limbs with a non-zero
+// top limb, and `dividend` at least as many limbs as `divisor`. Both are at
+// most 8 limbs long.
+fn long_divide(dividend: &[u64], divisor: &[u64], quotient: &mut [u64], remainder: &mut [u64]) {
+    let m = dividend.len();
+    let n = divisor.len();
+    debug_assert!(n >= 2 && m >= n && m <= 8 && divisor[n - 1] != 0);
+
+    // Normalize so the divisor's top bit is set, which keeps each quotient
+    // digit estimate within two of the true value.
+    let shift = divisor[n - 1].leading_zeros();
+    let mut v = [0u64; 8];
+    let mut u = [0u64; 9];
+    if shift == 0 {
+        v[..n].copy_from_slice(divisor);
+        u[..m].copy_from_slice(dividend);
+    } else {
+        for i in (1..n).rev() {
+            v[i] = (divisor[i] << shift) | (divisor[i - 1] >> (64 - shift));
+        }
+        v[0] = divisor[0] << shift;
+        u[m] = dividend[m - 1] >> (64 - shift);
+        for i in (1..m).rev() {
+            u[i] = (dividend[i] << shift) | (dividend[i - 1] >> (64 - shift));
+        }
+        u[0] = dividend[0] << shift;
+    }
+
+    let base: u128 = 1 << 64;
+    for j in (0..=m - n).rev() {
+        let numerator = (u[j + n] as u128) << 64 | u[j + n - 1] as u128;
+        let mut q_hat = numerator / v[n - 1] as u128;
+        let mut r_hat = numerator % v[n - 1] as u128;
+        while q_hat >= base || q_hat * v[n - 2] as u128 > (r_hat << 64 | u[j + n - 2] as u128) {
+            q_hat -= 1;
+            r_hat += v[n - 1] as u128;
+            if r_hat >= base {
+                break;
+            }
+        }
+
+        // Multiply and subtract q_hat * v from the current window of u.
+        let mut borrow: i128 = 0;
+        for i in 0..n {
+            let product = q_hat * v[i] as u128;
+            let t = u[i + j] as i128 - borrow - (product as u64) as i128;
+            u[i + j] = t as u64;
+            borrow = (product >> 64) as i128 - (t >> 64);
+        }
+        let t = u[j + n] as i128 - borrow;
+        u[j + n] = t as u64;
+
+        // The estimate was one too large; add the divisor back.
+        if t < 0 {
+            q_hat -= 1;
+            let mut carry = false;
+            for i in 0..n {
+                let (sum, next) = add_with_carry(u[i + j], v[i], carry);
+                u[i + j] = sum;
+                carry = next;
+            }
+            u[j + n] = u[j + n].wrapping_add(carry as u64);
+        }
+        quotient[j] = q_hat as u64;
+    }
+
+    for i in 0..n {
+        remainder[i] = if shift == 0 {
+            u[i]
+        } else {
+            (u[i] >> shift) | (u[i + 1] << (64 - shift))
+        };
+    }
+}
+
 impl UInt256 {
-    pub const ONE: UInt256 = UInt256 { high: 0, low: 1 };
-    pub const ZERO: UInt256 = UInt256 { high: 0, low: 0 };
+    pub const ONE: UInt256 = UInt256 {
+        limbs: [1, 0, 0, 0],
+    };
+    pub const ZERO: UInt256 = UInt256 {
+        limbs: [0, 0, 0, 0],
+    };
 
     pub fn from_bool(value: bool) -> UInt256 {
         if value {
@@ -35,80 +174,498 @@ impl UInt256 {
         }
     }
 
-    pub fn from_u128(value: u128) -> UInt256 {
+    pub const fn from_u128(value: u128) -> UInt256 {
+        UInt256::from_halves(0, value)
+    }
+
+    const fn from_halves(high: u128, low: u128) -> UInt256 {
         UInt256 {
-            low: value,
-            high: 0,
+            limbs: [
+                low as u64,
+                (low >> 64) as u64,
+                high as u64,
+                (high >> 64) as u64,
+            ],
         }
     }
 
+    pub fn from_limbs(limbs: [u64; 4]) -> UInt256 {
+        UInt256 { limbs }
+    }
+
+    /// The four u64 limbs, least significant first.
+    pub fn limbs(self) -> [u64; 4] {
+        self.limbs
+    }
+
+    #[inline]
+    fn high_u128(self) -> u128 {
+        (self.limbs[3] as u128) << 64 | self.limbs[2] as u128
+    }
+
+    #[inline]
+    fn low_u128(self) -> u128 {
+        (self.limbs[1] as u128) << 64 | self.limbs[0] as u128
+    }
+
+    #[inline]
+    fn fits_u64(self) -> bool {
+        self.limbs[1] | self.limbs[2] | self.limbs[3] == 0
+    }
+
+    #[inline]
+    fn fits_u128(self) -> bool {
+        self.limbs[2] | self.limbs[3] == 0
+    }
+
     pub fn from_string(hex: &str) -> UInt256 {
         UInt256::from_be_slice(&hex_string_as_vec_u8(hex))
     }
+
+    /// Adds modulo 2^256, returning whether the addition carried out of
+    /// the high limb.
+    #[inline]
+    pub fn overflowing_add(self, other: UInt256) -> (UInt256, bool) {
+        let mut limbs = [0u64; 4];
+        let mut carry = false;
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let (sum, next) = add_with_carry(self.limbs[i], other.limbs[i], carry);
+            *limb = sum;
+            carry = next;
+        }
+        (UInt256 { limbs }, carry)
+    }
+
+    pub fn checked_add(self, other: UInt256) -> Option<UInt256> {
+        match self.overflowing_add(other) {
+            (result, false) => Some(result),
+            (_, true) => None,
+        }
+    }
+
+    #[inline]
+    pub fn wrapping_add(self, other: UInt256) -> UInt256 {
+        self.overflowing_add(other).0
+    }
+
+    /// Subtracts modulo 2^256, returning whether the subtraction borrowed
+    /// out of the high limb (i.e. `other > self`).
+    #[inline]
+    pub fn overflowing_sub(self, other: UInt256) -> (UInt256, bool) {
+        let mut limbs = [0u64; 4];
+        let mut borrow = false;
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let (difference, next) = sub_with_borrow(self.limbs[i], other.limbs[i], borrow);
+            *limb = difference;
+            borrow = next;
+        }
+        (UInt256 { limbs }, borrow)
+    }
+
+    pub fn checked_sub(self, other: UInt256) -> Option<UInt256> {
+        match self.overflowing_sub(other) {
+            (result, false) => Some(result),
+            (_, true) => None,
+        }
+    }
+
+    #[inline]
+    pub fn wrapping_sub(self, other: UInt256) -> UInt256 {
+        self.overflowing_sub(other).0
+    }
+
+    pub fn leading_zeros(self) -> u32 {
+        match significant_limbs(&self.limbs) {
+            0 => 256,
+            n => (4 - n as u32) * 64 + self.limbs[n - 1].leading_zeros(),
+        }
+    }
+
+    fn bit(self, index: u32) -> bool {
+        (self.limbs[index as usize / 64] >> (index % 64)) & 1 == 1
+    }
+
+    /// Unsigned quotient and remainder. Following the Yellow Paper,
+    /// dividing by zero yields zero for both rather than panicking.
+    pub fn div_rem(self, divisor: UInt256) -> (UInt256, UInt256) {
+        if divisor == UInt256::ZERO {
+            return (UInt256::ZERO, UInt256::ZERO);
+        }
+        if self < divisor {
+            return (UInt256::ZERO, self);
+        }
+        if self.fits_u64() {
+            let (dividend, divisor) = (self.limbs[0], divisor.limbs[0]);
+            return (
+                UInt256::from(dividend / divisor),
+                UInt256::from(dividend % divisor),
+            );
+        }
+        if self.fits_u128() {
+            let (dividend, divisor) = (self.low_u128(), divisor.low_u128());
+            return (
+                UInt256::from_u128(dividend / divisor),
+                UInt256::from_u128(dividend % divisor),
+            );
+        }
+        if divisor.fits_u64() {
+            let mut quotient = self.limbs;
+            let remainder = div_rem_limb(&mut quotient, divisor.limbs[0]);
+            return (UInt256 { limbs: quotient }, UInt256::from(remainder));
+        }
+        let mut quotient = [0u64; 4];
+        let mut remainder = [0u64; 4];
+        let m = significant_limbs(&self.limbs);
+        let n = significant_limbs(&divisor.limbs);
+        long_divide(
+            &self.limbs[..m],
+            &divisor.limbs[..n],
+            &mut quotient,
+            &mut remainder,
+        );
+        (UInt256 { limbs: quotient }, UInt256 { limbs: remainder })
+    }
+}
+
+impl PartialOrd for UInt256 {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for UInt256 {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.high_u128(), self.low_u128()).cmp(&(other.high_u128(), other.low_u128()))
+    }
 }
 
 impl Add for UInt256 {
     type Output = Self;
 
+    #[inline]
     fn add(self, other: Self) -> Self {
-        let carry_result = self.low.overflowing_add(other.low);
-        let new_low = carry_result.0;
-        let mut new_high = self.high + other.high;
-        if carry_result.1 {
-            new_high += 1;
-        }
-        Self {
-            low: new_low,
-            high: new_high,
-        }
+        self.wrapping_add(other)
     }
 }
 
 impl Sub for UInt256 {
     type Output = Self;
 
+    #[inline]
     fn sub(self, other: Self) -> Self {
-        assert_eq!(self.high, 0);
-        assert_eq!(other.high, 0);
-        println!("Sub for UInt256 is a hack!");
-        Self {
-            low: self.low.checked_sub(other.low).expect("works"),
-            high: 0,
+        self.wrapping_sub(other)
+    }
+}
+
+impl Mul for UInt256 {
+    type Output = Self;
+
+    // Wraps modulo 2^256; partial products above the fourth limb are dropped.
+    #[inline]
+    fn mul(self, other: Self) -> Self {
+        if self.fits_u64() && other.fits_u64() {
+            return UInt256::from_u128(self.limbs[0] as u128 * other.limbs[0] as u128);
+        }
+        let a = self.limbs;
+        let b = other.limbs;
+        let mut limbs = [0u64; 4];
+        for i in 0..4 {
+            let mut carry: u128 = 0;
+            for j in 0..4 - i {
+                let product = a[i] as u128 * b[j] as u128 + limbs[i + j] as u128 + carry;
+                limbs[i + j] = product as u64;
+                carry = product >> 64;
+            }
         }
+        UInt256 { limbs }
+    }
+}
+
+impl Div for UInt256 {
+    type Output = Self;
+
+    fn div(self, divisor: Self) -> Self {
+        self.div_rem(divisor).0
+    }
+}
+
+impl Rem for UInt256 {
+    type Output = Self;
+
+    fn rem(self, divisor: Self) -> Self {
+        self.div_rem(divisor).1
     }
 }
 
 impl Not for UInt256 {
     type Output = Self;
 
+    #[inline]
     fn not(self) -> Self {
+        let [a, b, c, d] = self.limbs;
         Self {
-            low: !self.low,
-            high: !self.high,
+            limbs: [!a, !b, !c, !d],
+        }
+    }
+}
+
+impl UInt256 {
+    // Shift amounts of 256 or more shift every bit out of the word.
+    #[inline]
+    fn shift_amount(shift: UInt256) -> Option<u32> {
+        if shift.fits_u64() && shift.limbs[0] < 256 {
+            Some(shift.limbs[0] as u32)
+        } else {
+            None
+        }
+    }
+
+    /// Arithmetic (sign-filling) right shift, as used by SAR.
+    #[inline]
+    pub fn sar(self, shift: UInt256) -> UInt256 {
+        if self.is_negative() {
+            // Shifting the complement in zeros and complementing back
+            // fills the vacated bits with ones.
+            !(!self >> shift)
+        } else {
+            self >> shift
+        }
+    }
+}
+
+/// Double-width intermediate for ADDMOD and MULMOD, which must reduce the
+/// full sum or product before truncating to 256 bits.
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct UInt512 {
+    limbs: [u64; 8],
+}
+
+impl UInt512 {
+    pub fn high(self) -> UInt256 {
+        UInt256 {
+            limbs: [self.limbs[4], self.limbs[5], self.limbs[6], self.limbs[7]],
+        }
+    }
+
+    pub fn low(self) -> UInt256 {
+        UInt256 {
+            limbs: [self.limbs[0], self.limbs[1], self.limbs[2], self.limbs[3]],
         }
     }
+
+    fn from_halves(high: UInt256, low: UInt256) -> UInt512 {
+        let mut limbs = [0u64; 8];
+        limbs[..4].copy_from_slice(&low.limbs);
+        limbs[4..].copy_from_slice(&high.limbs);
+        UInt512 { limbs }
+    }
+
+    pub fn from_u256(value: UInt256) -> UInt512 {
+        UInt512::from_halves(UInt256::ZERO, value)
+    }
 }
 
+impl Rem<UInt256> for UInt512 {
+    type Output = UInt256;
+
+    /// Remainder modulo a 256-bit value; zero when `modulus` is zero.
+    fn rem(self, modulus: UInt256) -> UInt256 {
+        if self.high() == UInt256::ZERO {
+            return self.low() % modulus;
+        }
+        if modulus == UInt256::ZERO {
+            return UInt256::ZERO;
+        }
+        if modulus.fits_u64() {
+            let mut quotient = self.limbs;
+            return UInt256::from(div_rem_limb(&mut quotient, modulus.limbs[0]));
+        }
+        let mut quotient = [0u64; 8];
+        let mut remainder = [0u64; 4];
+        let m = significant_limbs(&self.limbs);
+        let n = significant_limbs(&modulus.limbs);
+        long_divide(
+            &self.limbs[..m],
+            &modulus.limbs[..n],
+            &mut quotient,
+            &mut remainder,
+        );
+        UInt256 { limbs: remainder }
+    }
+}
+
+impl UInt256 {
+    pub fn full_add(self, other: UInt256) -> UInt512 {
+        let (low, carry) = self.overflowing_add(other);
+        UInt512::from_halves(UInt256::from_bool(carry), low)
+    }
+
+    pub fn full_mul(self, other: UInt256) -> UInt512 {
+        let a = self.limbs;
+        let b = other.limbs;
+        let mut limbs = [0u64; 8];
+        for i in 0..4 {
+            let mut carry: u128 = 0;
+            for j in 0..4 {
+                let product = a[i] as u128 * b[j] as u128 + limbs[i + j] as u128 + carry;
+                limbs[i + j] = product as u64;
+                carry = product >> 64;
+            }
+            limbs[i + 4] = carry as u64;
+        }
+        UInt512 { limbs }
+    }
+
+    /// (self + other) % modulus without losing the carry; zero modulus
+    /// yields zero.
+    pub fn add_mod(self, other: UInt256, modulus: UInt256) -> UInt256 {
+        self.full_add(other) % modulus
+    }
+
+    /// (self * other) % modulus over the full 512-bit product; zero
+    /// modulus yields zero.
+    pub fn mul_mod(self, other: UInt256, modulus: UInt256) -> UInt256 {
+        self.full_mul(other) % modulus
+    }
+
+    /// Exponentiation modulo 2^256 by square-and-multiply.
+    pub fn pow(self, exponent: UInt256) -> UInt256 {
+        let mut result = UInt256::ONE;
+        let mut base = self;
+        for index in 0..256 - exponent.leading_zeros() {
+            if exponent.bit(index) {
+                result = result * base;
+            }
+            base = base * base;
+        }
+        result
+    }
+}
+
+// Two's complement view of UInt256, used by the signed opcodes.
+impl UInt256 {
+    pub const INT_MIN: UInt256 = UInt256 {
+        limbs: [0, 0, 0, 1 << 63],
+    };
+
+    #[inline]
+    pub fn is_negative(self) -> bool {
+        self.limbs[3] >> 63 == 1
+    }
+
+    #[inline]
+    pub fn negate(self) -> UInt256 {
+        (!self).wrapping_add(UInt256::ONE)
+    }
+
+    fn abs(self) -> UInt256 {
+        if self.is_negative() {
+            self.negate()
+        } else {
+            self
+        }
+    }
+
+    pub fn signed_cmp(self, other: UInt256) -> Ordering {
+        match (self.is_negative(), other.is_negative()) {
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            // Same sign: two's complement preserves unsigned ordering.
+            _ => self.cmp(&other),
+        }
+    }
+
+    /// Signed division truncating toward zero. INT_MIN / -1 overflows
+    /// back to INT_MIN, and division by zero yields zero.
+    pub fn signed_div(self, divisor: UInt256) -> UInt256 {
+        let quotient = self.abs() / divisor.abs();
+        if self.is_negative() != divisor.is_negative() {
+            quotient.negate()
+        } else {
+            quotient
+        }
+    }
+
+    /// Signed remainder whose sign follows the dividend. Remainder by zero
+    /// yields zero.
+    pub fn signed_rem(self, divisor: UInt256) -> UInt256 {
+        let remainder = self.abs() % divisor.abs();
+        if self.is_negative() {
+            remainder.negate()
+        } else {
+            remainder
+        }
+    }
+
+    /// Extends the sign bit of the byte at `byte_index` (counting from the
+    /// least significant byte) through the rest of the word.
+    pub fn sign_extend(self, byte_index: UInt256) -> UInt256 {
+        if byte_index >= UInt256::from_u128(31) {
+            return self;
+        }
+        let sign_bit = byte_index.limbs[0] as u32 * 8 + 7;
+        let mask = (UInt256::ONE << UInt256::from(sign_bit)) - UInt256::ONE;
+        if self.bit(sign_bit) {
+            self | !mask
+        } else {
+            self & mask
+        }
+    }
+}
+
+impl BitAnd for UInt256 {
+    type Output = Self;
+
+    #[inline]
+    fn bitand(self, other: Self) -> Self {
+        let (a, b) = (self.limbs, other.limbs);
+        Self {
+            limbs: [a[0] & b[0], a[1] & b[1], a[2] & b[2], a[3] & b[3]],
+        }
+    }
+}
+
+impl BitOr for UInt256 {
+    type Output = Self;
+
+    #[inline]
+    fn bitor(self, other: Self) -> Self {
+        let (a, b) = (self.limbs, other.limbs);
+        Self {
+            limbs: [a[0] | b[0], a[1] | b[1], a[2] | b[2], a[3] | b[3]],
+        }
+    }
+}
+
+impl BitXor for UInt256 {
+    type Output = Self;
+
+    #[inline]
+    fn bitxor(self, other: Self) -> Self {
+        let (a, b) = (self.limbs, other.limbs);
+        Self {
+            limbs: [a[0] ^ b[0], a[1] ^ b[1], a[2] ^ b[2], a[3] ^ b[3]],
+        }
+    }
+}
+
+// Shifts work on the two u128 halves, which LLVM lowers to SHLD/SHRD
+// pairs and beats shuffling individual limbs.
 impl Shr for UInt256 {
     type Output = Self;
 
+    #[inline]
     fn shr(self, shift: Self) -> Self {
-        // assert not shifting more than 256 (could return 0x0 instead)
-        assert_eq!(shift.high, 0);
-        assert!(shift.low < 256);
-        if shift.low > 128 {
-            println!("{} >> {}", self.high, shift.low - 128);
-            UInt256 {
-                high: 0,
-                low: self.high >> (shift.low - 128),
+        let (high, low) = (self.high_u128(), self.low_u128());
+        match UInt256::shift_amount(shift) {
+            None => UInt256::ZERO,
+            Some(0) => self,
+            Some(bits) if bits >= 128 => UInt256::from_halves(0, high >> (bits - 128)),
+            Some(bits) => {
+                UInt256::from_halves(high >> bits, (low >> bits) | (high << (128 - bits)))
             }
-        } else {
-            // This case is more complicated:
-            // Figure out how many bits apply to high vs. low
-            // Mask off any bits needed from high into low.
-            // Shift-right high-half.
-            // Rotate_right low-half.
-            panic!("Not implemented");
         }
     }
 }
@@ -116,71 +673,229 @@ impl Shr for UInt256 {
 impl Shl for UInt256 {
     type Output = Self;
 
+    #[inline]
     fn shl(self, shift: Self) -> Self {
-        // assert not shifting more than 256
-        assert_eq!(shift.high, 0);
-        assert!(shift.low < 256);
-        if shift.low > 128 {
-            UInt256 {
-                low: 0,
-                high: self.low << (shift.low - 128),
-            }
-        } else {
-            // This case is more complicated.
-            panic!("Not implemented");
+        let (high, low) = (self.high_u128(), self.low_u128());
+        match UInt256::shift_amount(shift) {
+            None => UInt256::ZERO,
+            Some(0) => self,
+            Some(bits) if bits >= 128 => UInt256::from_halves(low << (bits - 128), 0),
+            Some(bits) => UInt256::from_halves((high << bits) | (low >> (128 - bits)), low << bits),
         }
     }
 }
 
-impl TryFrom<UInt256> for usize {
+macro_rules! impl_try_from_uint256 {
+    ($($t:ty),*) => {$(
+        impl TryFrom<UInt256> for $t {
+            type Error = std::num::TryFromIntError;
+
+            fn try_from(value: UInt256) -> Result<Self, Self::Error> {
+                if !value.fits_u128() {
+                    // TryFromIntError is not directly construtable?
+                    // https://stackoverflow.com/questions/54374979/tryfrominterror-usage
+                    u128::MAX.try_into()
+                } else {
+                    Self::try_from(value.low_u128())
+                }
+            }
+        }
+    )*};
+}
+
+impl_try_from_uint256!(u8, u16, u32, u64, usize);
+
+impl TryFrom<UInt256> for u128 {
     type Error = std::num::TryFromIntError;
 
     fn try_from(value: UInt256) -> Result<Self, Self::Error> {
-        if value.high != 0 {
-            // TryFromIntError is not directly construtable?
-            // https://stackoverflow.com/questions/54374979/tryfrominterror-usage
-            u128::MAX.try_into()
+        if !value.fits_u128() {
+            // No u128 conversion can fail, so borrow the error from one that always does.
+            u128::try_from(-1i128)
         } else {
-            Self::try_from(value.low)
+            Ok(value.low_u128())
         }
     }
 }
 
+macro_rules! impl_from_uint {
+    ($($t:ty),*) => {$(
+        impl From<$t> for UInt256 {
+            fn from(value: $t) -> Self {
+                UInt256::from_u128(u128::from(value))
+            }
+        }
+    )*};
+}
+
+impl_from_uint!(u8, u16, u32, u64, u128);
+
+impl From<[u8; 32]> for UInt256 {
+    fn from(bytes: [u8; 32]) -> Self {
+        UInt256::from_be_slice(&bytes)
+    }
+}
+
+impl From<UInt256> for [u8; 32] {
+    fn from(value: UInt256) -> Self {
+        let mut bytes = [0u8; 32];
+        value.to_be_bytes(&mut bytes);
+        bytes
+    }
+}
+
 impl TryFrom<usize> for UInt256 {
     type Error = std::num::TryFromIntError;
 
     fn try_from(value: usize) -> Result<Self, Self::Error> {
-        let low: u128 = u128::try_from(value)?;
-        Ok(UInt256 { low: low, high: 0 })
+        Ok(UInt256::from_u128(u128::try_from(value)?))
     }
 }
 
-fn u128_from_be_slice(bytes: &[u8]) -> u128 {
-    let mut word: u128 = 0;
-    for byte in bytes {
-        word <<= 8;
-        word += *byte as u128;
+impl UInt256 {
+    /// Reads a big-endian value of up to 32 bytes; shorter slices are
+    /// zero-extended on the left, as PUSH immediates are.
+    pub fn from_be_slice(bytes: &[u8]) -> UInt256 {
+        let bytes = &bytes[bytes.len().saturating_sub(32)..];
+        let mut padded = [0u8; 32];
+        padded[32 - bytes.len()..].copy_from_slice(bytes);
+        let mut limbs = [0u64; 4];
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let end = 32 - i * 8;
+            *limb = u64::from_be_bytes(padded[end - 8..end].try_into().expect("8 bytes"));
+        }
+        UInt256 { limbs }
+    }
+
+    pub fn to_be_bytes(self, bytes: &mut [u8]) {
+        for (i, limb) in self.limbs.iter().enumerate() {
+            let end = 32 - i * 8;
+            bytes[end - 8..end].copy_from_slice(&limb.to_be_bytes());
+        }
+    }
+
+    /// The byte at `index` counting from the most significant byte, as
+    /// BYTE reads it. Indices past the end of the word read as zero.
+    pub fn byte(self, index: usize) -> u8 {
+        if index >= 32 {
+            return 0;
+        }
+        let limb = self.limbs[3 - index / 8];
+        (limb >> ((7 - index % 8) * 8)) as u8
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum ParseUInt256Error {
+    Empty,
+    InvalidDigit,
+    Overflow,
+    TooManyDecimals,
+}
+
+impl fmt::Debug for ParseUInt256Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseUInt256Error::Empty => write!(f, "Empty"),
+            ParseUInt256Error::InvalidDigit => write!(f, "InvalidDigit"),
+            ParseUInt256Error::Overflow => write!(f, "Overflow"),
+            ParseUInt256Error::TooManyDecimals => write!(f, "TooManyDecimals"),
+        }
     }
-    return word;
 }
 
 impl UInt256 {
-    pub fn from_be_slice(bytes: &[u8]) -> UInt256 {
-        if bytes.len() > 16 {
-            return UInt256 {
-                high: u128_from_be_slice(&bytes[..16]),
-                low: u128_from_be_slice(&bytes[16..]),
-            };
-        }
-        return UInt256 {
-            high: 0,
-            low: u128_from_be_slice(bytes),
-        };
+    pub fn from_str_radix(digits: &str, radix: u32) -> Result<UInt256, ParseUInt256Error> {
+        if digits.is_empty() {
+            return Err(ParseUInt256Error::Empty);
+        }
+        let radix_word = UInt256::from(radix);
+        let mut value = UInt256::ZERO;
+        for c in digits.chars() {
+            let digit = c.to_digit(radix).ok_or(ParseUInt256Error::InvalidDigit)?;
+            let shifted = value.full_mul(radix_word);
+            if shifted.high() != UInt256::ZERO {
+                return Err(ParseUInt256Error::Overflow);
+            }
+            value = shifted
+                .low()
+                .checked_add(UInt256::from(digit))
+                .ok_or(ParseUInt256Error::Overflow)?;
+        }
+        Ok(value)
+    }
+
+    /// Digits in `radix` (2 to 36) without a prefix, e.g. decimal for 10.
+    pub fn to_string_radix(self, radix: u32) -> String {
+        assert!((2..=36).contains(&radix));
+        if self == UInt256::ZERO {
+            return String::from("0");
+        }
+        let mut digits = Vec::new();
+        let mut limbs = self.limbs;
+        while significant_limbs(&limbs) != 0 {
+            let digit = div_rem_limb(&mut limbs, radix as u64);
+            digits.push(std::char::from_digit(digit as u32, radix).expect("digit < radix"));
+        }
+        digits.iter().rev().collect()
+    }
+}
+
+/// Accepts decimal, or hex with a `0x` prefix.
+impl FromStr for UInt256 {
+    type Err = ParseUInt256Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            Some(hex) => UInt256::from_str_radix(hex, 16),
+            None => UInt256::from_str_radix(s, 10),
+        }
+    }
+}
+
+impl fmt::LowerHex for UInt256 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_integral(true, "0x", &self.to_string_radix(16))
+    }
+}
+
+impl fmt::UpperHex for UInt256 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_integral(true, "0x", &self.to_string_radix(16).to_uppercase())
+    }
+}
+
+// Words serialize as canonical lowercase 0x-prefixed hex and deserialize
+// from either hex or decimal strings (or plain JSON integers).
+impl Serialize for UInt256 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{:#x}", self))
+    }
+}
+
+struct UInt256Visitor;
+
+impl<'de> Visitor<'de> for UInt256Visitor {
+    type Value = UInt256;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a 0x-prefixed hex or decimal string")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<UInt256, E> {
+        value
+            .parse()
+            .map_err(|e| E::custom(format!("invalid UInt256 {:?}: {:?}", value, e)))
     }
 
-    pub fn to_be_bytes(&self, bytes: &mut [u8]) {
-        bytes[..16].copy_from_slice(&self.high.to_be_bytes());
-        bytes[16..].copy_from_slice(&self.low.to_be_bytes());
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<UInt256, E> {
+        Ok(UInt256::from(value))
+    }
+}
+
+impl<'de> Deserialize<'de> for UInt256 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(UInt256Visitor)
     }
 }
 
@@ -192,24 +907,22 @@ impl fmt::Debug for UInt256 {
 
 impl fmt::Display for UInt256 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if self.high == 0 {
-            write!(f, "0x{:02X}", self.low)
-        } else {
-            write!(f, "0x{:X}{:032X}", self.high, self.low)
-        }
+        write!(f, "0x{:02X}", self)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::UInt256;
+    use super::{ParseUInt256Error, UInt256, UInt512};
+    use std::cmp::Ordering;
+    use std::convert::TryFrom;
     #[test]
     fn ordering_works() {
         assert!(UInt256::ONE > UInt256::ZERO);
         assert!(UInt256::ZERO < UInt256::ONE);
 
-        let bigger = UInt256 { high: 2, low: 1 };
-        let smaller = UInt256 { high: 1, low: 2 };
+        let bigger = UInt256::from_halves(2, 1);
+        let smaller = UInt256::from_halves(1, 2);
         assert!(bigger > smaller);
     }
     #[test]
@@ -219,26 +932,342 @@ mod tests {
         );
         let expected_high: u128 = 0x6D4CE63C << 96;
         let expected_low = 0x00000000;
-        assert_eq!(value.low, expected_low);
-        assert_eq!(value.high, expected_high);
+        assert_eq!(value, UInt256::from_halves(expected_high, expected_low));
     }
     #[test]
     fn shr_works() {
-        let value = UInt256 {
-            high: 0x6D4CE63C << 96,
-            low: 0,
-        };
+        let value = UInt256::from_halves(0x6D4CE63C << 96, 0);
         let shift = UInt256::from_u128(0xE0);
         let expected = 0x6D4CE63C;
         assert_eq!(value >> shift, UInt256::from_u128(expected));
     }
     #[test]
     fn not_works() {
-        let value = UInt256 { high: 1, low: 0 };
-        let expected = UInt256 {
-            high: u128::MAX - 1,
-            low: u128::MAX,
-        };
+        let value = UInt256::from_halves(1, 0);
+        let expected = UInt256::from_halves(u128::MAX - 1, u128::MAX);
         assert_eq!(!value, expected);
     }
+    #[test]
+    fn sub_borrows_across_halves() {
+        let value = UInt256::from_halves(1, 0);
+        let expected = UInt256::from_halves(0, u128::MAX);
+        assert_eq!(value - UInt256::ONE, expected);
+    }
+    #[test]
+    fn sub_wraps_on_underflow() {
+        let max = !UInt256::ZERO;
+        assert_eq!(UInt256::ZERO - UInt256::ONE, max);
+        assert_eq!(UInt256::ZERO.overflowing_sub(UInt256::ONE), (max, true));
+        assert_eq!(UInt256::ONE.checked_sub(UInt256::from_u128(2)), None);
+        assert_eq!(
+            UInt256::from_u128(5).checked_sub(UInt256::from_u128(3)),
+            Some(UInt256::from_u128(2))
+        );
+        assert_eq!(max.wrapping_sub(max), UInt256::ZERO);
+    }
+    #[test]
+    fn shifts_cross_halves() {
+        let value = UInt256::from_halves(0x1, 0x8000_0000_0000_0000_0000_0000_0000_0001);
+        let one = UInt256::ONE;
+        assert_eq!(
+            value >> one,
+            UInt256::from_halves(0, (1 << 127) | (1 << 126))
+        );
+        assert_eq!(value << one, UInt256::from_halves(0x3, 0x2));
+        assert_eq!(value >> UInt256::from_u128(128), one);
+        assert_eq!(
+            one << UInt256::from_u128(255),
+            UInt256::from_halves(1 << 127, 0)
+        );
+        assert_eq!(value >> UInt256::ZERO, value);
+        assert_eq!(value << UInt256::ZERO, value);
+    }
+    #[test]
+    fn shifts_of_256_or_more_are_zero() {
+        let max = !UInt256::ZERO;
+        assert_eq!(max >> UInt256::from_u128(256), UInt256::ZERO);
+        assert_eq!(max << UInt256::from_u128(256), UInt256::ZERO);
+        assert_eq!(max << UInt256::from_halves(1, 0), UInt256::ZERO);
+    }
+    #[test]
+    fn sar_fills_sign() {
+        let max = !UInt256::ZERO;
+        let min = UInt256::from_halves(1 << 127, 0);
+        assert_eq!(max.sar(UInt256::from_u128(4)), max);
+        assert_eq!(min.sar(UInt256::from_u128(255)), max);
+        assert_eq!(min.sar(UInt256::from_u128(300)), max);
+        assert_eq!(
+            min.sar(UInt256::from_u128(128)),
+            UInt256::from_halves(u128::MAX, 1 << 127)
+        );
+        let positive = UInt256::from_u128(0x100);
+        assert_eq!(
+            positive.sar(UInt256::from_u128(4)),
+            UInt256::from_u128(0x10)
+        );
+        assert_eq!(positive.sar(UInt256::from_u128(256)), UInt256::ZERO);
+    }
+    #[test]
+    fn mul_works() {
+        let a = UInt256::from_u128(u128::MAX);
+        let b = UInt256::from_u128(2);
+        let expected = UInt256::from_halves(1, u128::MAX - 1);
+        assert_eq!(a * b, expected);
+        assert_eq!(a * a, UInt256::from_halves(u128::MAX - 1, 1));
+        assert_eq!(a * UInt256::ZERO, UInt256::ZERO);
+    }
+    #[test]
+    fn mul_wraps() {
+        let max = !UInt256::ZERO;
+        assert_eq!(max * max, UInt256::ONE);
+        let high_bit = UInt256::from_halves(1 << 127, 0);
+        assert_eq!(high_bit * UInt256::from_u128(2), UInt256::ZERO);
+    }
+    #[test]
+    fn div_and_rem_work() {
+        let seven = UInt256::from_u128(7);
+        let two = UInt256::from_u128(2);
+        assert_eq!(seven / two, UInt256::from_u128(3));
+        assert_eq!(seven % two, UInt256::ONE);
+
+        let big = UInt256::from_halves(5, 3);
+        let divisor = UInt256::from_halves(1, 0);
+        assert_eq!(big / divisor, UInt256::from_u128(5));
+        assert_eq!(big % divisor, UInt256::from_u128(3));
+
+        let max = !UInt256::ZERO;
+        assert_eq!(max / max, UInt256::ONE);
+        assert_eq!(
+            max / UInt256::from_u128(u128::MAX),
+            UInt256::from_halves(1, 1)
+        );
+        assert_eq!(max % UInt256::from_u128(u128::MAX), UInt256::ZERO);
+    }
+    #[test]
+    fn div_by_zero_is_zero() {
+        let value = UInt256::from_halves(1, 1);
+        assert_eq!(value / UInt256::ZERO, UInt256::ZERO);
+        assert_eq!(value % UInt256::ZERO, UInt256::ZERO);
+    }
+    #[test]
+    fn add_wraps_on_overflow() {
+        let max = !UInt256::ZERO;
+        assert_eq!(max + UInt256::ONE, UInt256::ZERO);
+        assert_eq!(max.overflowing_add(UInt256::ONE), (UInt256::ZERO, true));
+        assert_eq!(max.checked_add(UInt256::ONE), None);
+        let carry = UInt256::from_u128(u128::MAX) + UInt256::ONE;
+        assert_eq!(carry, UInt256::from_halves(1, 0));
+    }
+    #[test]
+    fn signed_compare_works() {
+        let minus_one = !UInt256::ZERO;
+        let one = UInt256::ONE;
+        assert_eq!(minus_one.signed_cmp(one), Ordering::Less);
+        assert_eq!(one.signed_cmp(minus_one), Ordering::Greater);
+        assert_eq!(UInt256::INT_MIN.signed_cmp(minus_one), Ordering::Less);
+        assert_eq!(minus_one.signed_cmp(minus_one), Ordering::Equal);
+        let int_max = !UInt256::INT_MIN;
+        assert_eq!(int_max.signed_cmp(UInt256::INT_MIN), Ordering::Greater);
+    }
+    #[test]
+    fn signed_div_works() {
+        let minus_one = !UInt256::ZERO;
+        let seven = UInt256::from_u128(7);
+        let two = UInt256::from_u128(2);
+        assert_eq!(
+            seven.negate().signed_div(two),
+            UInt256::from_u128(3).negate()
+        );
+        assert_eq!(
+            seven.negate().signed_div(two.negate()),
+            UInt256::from_u128(3)
+        );
+        assert_eq!(seven.signed_div(UInt256::ZERO), UInt256::ZERO);
+        // Yellow Paper: -2^255 / -1 overflows to -2^255.
+        assert_eq!(UInt256::INT_MIN.signed_div(minus_one), UInt256::INT_MIN);
+    }
+    #[test]
+    fn signed_rem_follows_dividend_sign() {
+        let seven = UInt256::from_u128(7);
+        let three = UInt256::from_u128(3);
+        assert_eq!(seven.negate().signed_rem(three), UInt256::ONE.negate());
+        assert_eq!(seven.signed_rem(three.negate()), UInt256::ONE);
+        assert_eq!(seven.negate().signed_rem(UInt256::ZERO), UInt256::ZERO);
+        assert_eq!(UInt256::INT_MIN.signed_rem(!UInt256::ZERO), UInt256::ZERO);
+    }
+    #[test]
+    fn sign_extend_works() {
+        let byte = UInt256::from_u128(0xFF);
+        assert_eq!(byte.sign_extend(UInt256::ZERO), !UInt256::ZERO);
+        let positive = UInt256::from_u128(0x7F);
+        assert_eq!(positive.sign_extend(UInt256::ZERO), positive);
+        let dirty = UInt256::from_u128(0x1234_7FFF);
+        assert_eq!(dirty.sign_extend(UInt256::ONE), UInt256::from_u128(0x7FFF));
+        let value = UInt256::from_u128(0x8000);
+        assert_eq!(value.sign_extend(UInt256::from_u128(31)), value);
+        assert_eq!(value.sign_extend(UInt256::from_halves(1, 0)), value);
+        assert_eq!(
+            value.sign_extend(UInt256::ONE),
+            UInt256::from_u128(0x8000) - UInt256::from_u128(0x10000)
+        );
+    }
+    #[test]
+    fn full_mul_keeps_high_half() {
+        let max = !UInt256::ZERO;
+        // (2^256 - 1)^2 = 2^512 - 2^257 + 1
+        let expected = UInt512::from_halves(max - UInt256::ONE, UInt256::ONE);
+        assert_eq!(max.full_mul(max), expected);
+        assert_eq!(max.full_add(max).high(), UInt256::ONE);
+    }
+    #[test]
+    fn add_mod_keeps_carry() {
+        let max = !UInt256::ZERO;
+        let ten = UInt256::from_u128(10);
+        // (2^256 - 1) * 2 = 2^257 - 2, and 2^256 = 6 (mod 10).
+        assert_eq!(max.add_mod(max, ten), UInt256::from_u128(0));
+        assert_eq!(
+            max.add_mod(UInt256::from_u128(2), max),
+            UInt256::from_u128(2)
+        );
+        assert_eq!(
+            UInt256::from_u128(8).add_mod(UInt256::from_u128(5), ten),
+            UInt256::from_u128(3)
+        );
+        assert_eq!(max.add_mod(max, UInt256::ZERO), UInt256::ZERO);
+    }
+    #[test]
+    fn mul_mod_keeps_high_half() {
+        let max = !UInt256::ZERO;
+        assert_eq!(
+            max.mul_mod(max, UInt256::from_u128(12)),
+            UInt256::from_u128(9)
+        );
+        assert_eq!(max.mul_mod(UInt256::from_u128(2), max), UInt256::ZERO);
+        assert_eq!(max.mul_mod(max, max - UInt256::ONE), UInt256::ONE);
+        assert_eq!(max.mul_mod(max, UInt256::ZERO), UInt256::ZERO);
+    }
+    #[test]
+    fn pow_works() {
+        let two = UInt256::from_u128(2);
+        assert_eq!(two.pow(UInt256::from_u128(10)), UInt256::from_u128(1024));
+        assert_eq!(two.pow(UInt256::from_u128(255)), UInt256::INT_MIN);
+        assert_eq!(two.pow(UInt256::from_u128(256)), UInt256::ZERO);
+        assert_eq!(UInt256::ZERO.pow(UInt256::ZERO), UInt256::ONE);
+        let max = !UInt256::ZERO;
+        assert_eq!(max.pow(UInt256::from_u128(3)), max);
+        assert_eq!(
+            UInt256::from_u128(3).pow(max),
+            UInt256::from_u128(3).pow(max - two) * UInt256::from_u128(9)
+        );
+    }
+    #[test]
+    fn bit_operators_work() {
+        let a = UInt256::from_halves(0b1100, 0b1010);
+        let b = UInt256::from_halves(0b1010, 0b0110);
+        assert_eq!(a & b, UInt256::from_halves(0b1000, 0b0010));
+        assert_eq!(a | b, UInt256::from_halves(0b1110, 0b1110));
+        assert_eq!(a ^ b, UInt256::from_halves(0b0110, 0b1100));
+        let selector = UInt256::from_string("6D4CE63C");
+        let word = (selector << UInt256::from_u128(224)) | UInt256::ONE;
+        let mask = UInt256::from_u128(0xFFFFFFFF);
+        assert_eq!((word >> UInt256::from_u128(224)) & mask, selector);
+    }
+    #[test]
+    fn byte_works() {
+        let value = UInt256::from_string(
+            "0102030405060708090A0B0C0D0E0F101112131415161718191A1B1C1D1E1F20",
+        );
+        assert_eq!(value.byte(0), 0x01);
+        assert_eq!(value.byte(15), 0x10);
+        assert_eq!(value.byte(16), 0x11);
+        assert_eq!(value.byte(31), 0x20);
+        assert_eq!(value.byte(32), 0);
+    }
+    #[test]
+    fn from_str_parses_decimal_and_hex() {
+        let max = !UInt256::ZERO;
+        let max_decimal =
+            "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+        assert_eq!(max_decimal.parse::<UInt256>(), Ok(max));
+        assert_eq!("1024".parse::<UInt256>(), Ok(UInt256::from_u128(1024)));
+        assert_eq!("0x400".parse::<UInt256>(), Ok(UInt256::from_u128(1024)));
+        assert_eq!("0XfF".parse::<UInt256>(), Ok(UInt256::from_u128(255)));
+        assert_eq!("".parse::<UInt256>(), Err(ParseUInt256Error::Empty));
+        assert_eq!("0x".parse::<UInt256>(), Err(ParseUInt256Error::Empty));
+        assert_eq!(
+            "12a".parse::<UInt256>(),
+            Err(ParseUInt256Error::InvalidDigit)
+        );
+        let too_big =
+            "115792089237316195423570985008687907853269984665640564039457584007913129639936";
+        assert_eq!(too_big.parse::<UInt256>(), Err(ParseUInt256Error::Overflow));
+        let too_long = format!("0x1{}", "0".repeat(64));
+        assert_eq!(
+            too_long.parse::<UInt256>(),
+            Err(ParseUInt256Error::Overflow)
+        );
+    }
+    #[test]
+    fn to_string_radix_works() {
+        let max = !UInt256::ZERO;
+        assert_eq!(
+            max.to_string_radix(10),
+            "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+        );
+        assert_eq!(UInt256::ZERO.to_string_radix(10), "0");
+        assert_eq!(UInt256::from_u128(5).to_string_radix(2), "101");
+        assert_eq!(max.to_string_radix(16), "f".repeat(64));
+    }
+    #[test]
+    fn hex_formatting_works() {
+        let value = UInt256::from_halves(0xAB, 0xCD);
+        let digits = format!("ab{:032x}", 0xCD);
+        assert_eq!(format!("{:x}", value), digits);
+        assert_eq!(format!("{:#x}", value), format!("0x{}", digits));
+        assert_eq!(format!("{:X}", value), digits.to_uppercase());
+        assert_eq!(format!("{:#06x}", UInt256::from_u128(0xF)), "0x000f");
+        assert_eq!(format!("{:x}", UInt256::ZERO), "0");
+    }
+    #[test]
+    fn int_conversions_work() {
+        assert_eq!(UInt256::from(7u8), UInt256::from_u128(7));
+        assert_eq!(
+            UInt256::from(u64::MAX),
+            UInt256::from_u128(u64::MAX as u128)
+        );
+        assert_eq!(u64::try_from(UInt256::from(42u64)), Ok(42));
+        assert!(u8::try_from(UInt256::from(256u16)).is_err());
+        assert_eq!(u128::try_from(UInt256::from(u128::MAX)), Ok(u128::MAX));
+        assert!(u128::try_from(UInt256::from_halves(1, 0)).is_err());
+        assert!(u32::try_from(UInt256::from_halves(1, 0)).is_err());
+    }
+    #[test]
+    fn byte_array_conversions_work() {
+        let mut bytes = [0u8; 32];
+        bytes[0] = 0xAB;
+        bytes[31] = 0x01;
+        let value = UInt256::from(bytes);
+        assert_eq!(value, UInt256::from_halves(0xAB << 120, 1));
+        let round_trip: [u8; 32] = value.into();
+        assert_eq!(round_trip, bytes);
+    }
+    #[test]
+    fn serde_round_trips() {
+        let value = UInt256::from_halves(0x1, 0xFF);
+        let json = serde_json::to_string(&value).expect("serializes");
+        assert_eq!(json, format!("\"0x1{:032x}\"", 0xFF));
+        assert_eq!(
+            serde_json::from_str::<UInt256>(&json).expect("parses"),
+            value
+        );
+        assert_eq!(
+            serde_json::from_str::<UInt256>("\"1024\"").expect("parses"),
+            UInt256::from(1024u32)
+        );
+        assert_eq!(
+            serde_json::from_str::<UInt256>("1024").expect("parses"),
+            UInt256::from(1024u32)
+        );
+        assert!(serde_json::from_str::<UInt256>("\"0xZZ\"").is_err());
+    }
 }
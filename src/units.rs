@@ -0,0 +1,106 @@
+use crate::uint256::{ParseUInt256Error, UInt256};
+
+/// Denominations of ether; every amount on chain is held in wei.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Unit {
+    Wei,
+    Gwei,
+    Ether,
+}
+
+impl Unit {
+    pub fn decimals(self) -> u32 {
+        match self {
+            Unit::Wei => 0,
+            Unit::Gwei => 9,
+            Unit::Ether => 18,
+        }
+    }
+
+    // Number of wei in one of this unit.
+    fn scale(self) -> UInt256 {
+        UInt256::from_u128(10u128.pow(self.decimals()))
+    }
+}
+
+/// Formats a wei amount as a decimal in `unit`, e.g. 1.5 for 1.5 ether.
+pub fn format_units(wei: UInt256, unit: Unit) -> String {
+    let (whole, fraction) = wei.div_rem(unit.scale());
+    if fraction == UInt256::ZERO {
+        return whole.to_string_radix(10);
+    }
+    let fraction_digits = format!(
+        "{:0>width$}",
+        fraction.to_string_radix(10),
+        width = unit.decimals() as usize
+    );
+    format!(
+        "{}.{}",
+        whole.to_string_radix(10),
+        fraction_digits.trim_end_matches('0')
+    )
+}
+
+/// Parses a decimal amount in `unit` into wei, e.g. "1.5" ether.
+pub fn parse_units(amount: &str, unit: Unit) -> Result<UInt256, ParseUInt256Error> {
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    let decimals = unit.decimals() as usize;
+    if fraction.len() > decimals {
+        return Err(ParseUInt256Error::TooManyDecimals);
+    }
+    let whole = match whole {
+        "" if !fraction.is_empty() => UInt256::ZERO,
+        _ => UInt256::from_str_radix(whole, 10)?,
+    };
+    let fraction = match fraction {
+        "" => UInt256::ZERO,
+        _ => UInt256::from_str_radix(&format!("{:0<width$}", fraction, width = decimals), 10)?,
+    };
+    let scaled = whole.full_mul(unit.scale());
+    if scaled.high() != UInt256::ZERO {
+        return Err(ParseUInt256Error::Overflow);
+    }
+    scaled
+        .low()
+        .checked_add(fraction)
+        .ok_or(ParseUInt256Error::Overflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_units_works() {
+        let wei = UInt256::from_u128(1_500_000_000_000_000_000);
+        assert_eq!(format_units(wei, Unit::Ether), "1.5");
+        assert_eq!(format_units(wei, Unit::Gwei), "1500000000");
+        assert_eq!(format_units(wei, Unit::Wei), "1500000000000000000");
+        assert_eq!(
+            format_units(UInt256::ONE, Unit::Ether),
+            "0.000000000000000001"
+        );
+        assert_eq!(format_units(UInt256::ZERO, Unit::Ether), "0");
+    }
+
+    #[test]
+    fn parse_units_works() {
+        let wei = UInt256::from_u128(1_500_000_000_000_000_000);
+        assert_eq!(parse_units("1.5", Unit::Ether), Ok(wei));
+        assert_eq!(parse_units("1500000000", Unit::Gwei), Ok(wei));
+        assert_eq!(
+            parse_units(".5", Unit::Gwei),
+            Ok(UInt256::from_u128(500_000_000))
+        );
+        assert_eq!(parse_units("2", Unit::Wei), Ok(UInt256::from_u128(2)));
+        assert_eq!(
+            parse_units("0.5", Unit::Wei),
+            Err(ParseUInt256Error::TooManyDecimals)
+        );
+        assert_eq!(
+            parse_units("1.x", Unit::Ether),
+            Err(ParseUInt256Error::InvalidDigit)
+        );
+        assert_eq!(parse_units(".", Unit::Ether), Err(ParseUInt256Error::Empty));
+    }
+}
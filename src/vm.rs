@@ -1,50 +1,71 @@
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::convert::{TryFrom, TryInto};
 use std::fmt;
 use std::iter::Iterator;
+use std::mem;
 use std::ops::Range;
 
 use crate::instructions::*;
-use crate::storage::{Storage, StorageError};
+use crate::state::{create2_address, create_address, Checkpoint, Log};
 use crate::uint256::*;
-use crate::Message;
+use crate::units::{format_units, Unit};
+use crate::{keccak256, Address, Env, Message, WorldState};
 
 #[derive(Copy, Clone, PartialEq, Eq)]
 pub enum VMError {
     StackUnderflow,
-    EndOfInstructions,
-    BadAccess,
+    StackOverflow,
     BadOp(u8),
     BadArg,
+    InvalidJump { from: usize, to: UInt256 },
+    OutOfGas,
     OutOfBounds,
     TypeConversion,
-    StorageError(StorageError),
+    WriteProtection,
+    CodeSizeExceeded,
+    AddressCollision,
 }
 
 impl fmt::Debug for VMError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             VMError::StackUnderflow => write!(f, "STACK_UNDERFLOW"),
+            VMError::StackOverflow => write!(f, "STACK_OVERFLOW"),
             VMError::BadOp(op) => write!(f, "BadOp(0x{:02X})", op),
             VMError::BadArg => write!(f, "BadArg"),
-            VMError::BadAccess => write!(f, "BadAccess"),
+            VMError::InvalidJump { from, to } => {
+                write!(f, "InvalidJump(from: {:02X} to: {})", from, to)
+            }
             VMError::OutOfBounds => write!(f, "OutOfBounds"),
-            VMError::EndOfInstructions => write!(f, "END_OF_INSTRUCTIONS"),
+            VMError::OutOfGas => write!(f, "OUT_OF_GAS"),
             VMError::TypeConversion => write!(f, "TypeConversion"),
-            VMError::StorageError(e) => write!(f, "StorageError ({:?})", e),
+            VMError::WriteProtection => write!(f, "WriteProtection"),
+            VMError::CodeSizeExceeded => write!(f, "CodeSizeExceeded"),
+            VMError::AddressCollision => write!(f, "AddressCollision"),
         }
     }
 }
 
 type Word = UInt256;
 
-#[derive(Default)]
+// The EVM halts any push that would grow the stack past this many items.
+const STACK_LIMIT: usize = 1024;
+
 struct Stack {
     values: Vec<Word>,
 }
 
+impl Default for Stack {
+    fn default() -> Self {
+        Stack {
+            values: Vec::with_capacity(STACK_LIMIT),
+        }
+    }
+}
+
 impl Stack {
-    fn from_top(&self, index: usize) -> Result<usize, VMError> {
+    fn index_from_top(&self, index: usize) -> Result<usize, VMError> {
         if index < self.values.len() {
             Ok(self.values.len() - index - 1)
         } else {
@@ -52,22 +73,27 @@ impl Stack {
         }
     }
 
-    fn push(&mut self, value: UInt256) {
-        return self.values.push(value);
+    fn push(&mut self, value: UInt256) -> Result<(), VMError> {
+        if self.values.len() >= STACK_LIMIT {
+            return Err(VMError::StackOverflow);
+        }
+        self.values.push(value);
+        Ok(())
     }
 
     fn peek(&self, index: usize) -> Result<UInt256, VMError> {
-        Ok(self.values[self.from_top(index)?])
+        Ok(self.values[self.index_from_top(index)?])
     }
 
     fn pop(&mut self) -> Result<UInt256, VMError> {
-        return self.values.pop().ok_or(VMError::StackUnderflow);
+        self.values.pop().ok_or(VMError::StackUnderflow)
     }
 
     fn swap(&mut self, a: usize, b: usize) -> Result<(), VMError> {
-        let a_usize = self.from_top(a)?;
-        let b_usize = self.from_top(b)?;
-        Ok(self.values.swap(a_usize, b_usize))
+        let a_usize = self.index_from_top(a)?;
+        let b_usize = self.index_from_top(b)?;
+        self.values.swap(a_usize, b_usize);
+        Ok(())
     }
 }
 
@@ -87,57 +113,238 @@ struct Memory {
     bytes: Vec<u8>,
 }
 
-pub struct Task<'a> {
-    message: &'a Message, // This ends up being a stack I think?
+#[derive(Default)]
+struct Gas {
+    limit: u64,
+    remaining: u64,
+}
+
+impl Gas {
+    fn new(limit: u64) -> Gas {
+        Gas {
+            limit,
+            remaining: limit,
+        }
+    }
+
+    // Running out of gas consumes everything that was left.
+    fn consume(&mut self, amount: u64) -> Result<(), VMError> {
+        if amount > self.remaining {
+            self.remaining = 0;
+            return Err(VMError::OutOfGas);
+        }
+        self.remaining -= amount;
+        Ok(())
+    }
+
+    fn used(&self) -> u64 {
+        self.limit - self.remaining
+    }
+
+    fn refund(&mut self, amount: u64) {
+        self.remaining += amount;
+    }
+}
+
+// Everything that belongs to a single call. A callee starts with its own
+// memory, stack and gas; only its output and leftover gas flow back to
+// the caller.
+#[derive(Default)]
+struct Frame {
+    message: Message,
     stack: Stack,
     memory: Memory,
     input: InputManager,
-    storage: Storage,
+    gas: Gas,
+    // Output of the most recent call made from this frame.
+    return_data: Vec<u8>,
+    // Set by STATICCALL and inherited by every call made beneath it.
+    is_static: bool,
+}
+
+impl Frame {
+    fn new(input: InputManager, message: Message, is_static: bool) -> Frame {
+        Frame {
+            gas: Gas::new(message.gas_limit),
+            input,
+            message,
+            is_static,
+            ..Frame::default()
+        }
+    }
+}
+
+// What a suspended frame does with its callee's output.
+enum FrameKind {
+    // Copies it into this range of memory.
+    Call { return_range: Range<usize> },
+    // Deploys it as the code of the account being created.
+    Create { address: Address },
+}
+
+// A frame waiting on its callee, and the checkpoint taken when the
+// callee started, to commit or revert once it finishes.
+struct SuspendedFrame {
+    frame: Frame,
+    kind: FrameKind,
+    checkpoint: Checkpoint,
+}
+
+// A call made at this depth fails instead of running.
+const CALL_DEPTH_LIMIT: usize = 1024;
+// EIP-170 caps deployed code, and EIP-3860 caps init code at twice that.
+const MAX_CODE_SIZE: usize = 24576;
+const MAX_INITCODE_SIZE: usize = 2 * MAX_CODE_SIZE;
+
+pub struct Task<'a> {
+    env: &'a Env,
+    state: &'a mut WorldState,
+    // The frame currently executing, and the frames that called it,
+    // outermost first.
+    frame: Frame,
+    callers: Vec<SuspendedFrame>,
 }
 
 impl Task<'_> {
-    fn new(input: InputManager, message: &Message) -> Task {
+    fn new<'a>(
+        input: InputManager,
+        message: Message,
+        env: &'a Env,
+        state: &'a mut WorldState,
+    ) -> Task<'a> {
         Task {
-            input: input,
-            message: message,
-            stack: Stack::default(),
-            memory: Memory::default(),
-            storage: Storage::default(),
+            env,
+            state,
+            frame: Frame::new(input, message, false),
+            callers: Vec::new(),
         }
     }
 }
 
-fn to_usize_range(range: Range<UInt256>) -> Result<Range<usize>, VMError> {
-    let start = usize::try_from(range.start).map_err(|_| VMError::OutOfBounds)?;
-    let end = usize::try_from(range.end).map_err(|_| VMError::OutOfBounds)?;
-    Ok(start..end)
+// Total gas for a memory of `words` 32-byte words: linear for small
+// memories, quadratic once it grows large.
+fn memory_cost(words: u64) -> Result<u64, VMError> {
+    words
+        .checked_mul(words)
+        .map(|square| G_MEMORY * words + square / 512)
+        .ok_or(VMError::OutOfGas)
+}
+
+// Instructions that may never run inside a STATICCALL. CALL is also
+// forbidden there, but only when it sends value.
+fn modifies_state(instruction: &Instruction) -> bool {
+    matches!(
+        *instruction,
+        OP_SSTORE
+            | OP_TSTORE
+            | OP_CREATE
+            | OP_CREATE2
+            | OP_SELFDESTRUCT
+            | Instruction {
+                op: 0xA0..=0xA4,
+                ..
+            }
+    )
+}
+
+// Whether a contract already lives at `address`. Creating one there
+// fails, though the address may already hold a balance.
+fn is_occupied(state: &WorldState, address: Address) -> bool {
+    state.nonce(address) != 0 || !state.code(address).is_empty()
+}
+
+// EIP-2929: the first access to an account in a transaction costs more
+// than the warm price the instruction already charged.
+fn charge_account_access(
+    state: &mut WorldState,
+    gas: &mut Gas,
+    address: Address,
+) -> Result<(), VMError> {
+    if state.warm_account(address) {
+        gas.consume(G_COLDACCOUNTACCESS - G_WARMACCESS)?;
+    }
+    Ok(())
+}
+
+// Makes the output of init code the new account's code, charging for
+// every byte stored.
+fn deposit_code(
+    state: &mut WorldState,
+    gas: &mut Gas,
+    address: Address,
+    code: Vec<u8>,
+) -> Result<(), VMError> {
+    if code.len() > MAX_CODE_SIZE {
+        return Err(VMError::CodeSizeExceeded);
+    }
+    gas.consume(G_CODEDEPOSIT * code.len() as u64)?;
+    state.set_code(address, code);
+    Ok(())
+}
+
+fn word_count(length: usize) -> u64 {
+    length.div_ceil(32) as u64
+}
+
+// Copies `source[offset..]` into `dest`, reading past the end of `source`
+// as zeros.
+fn copy_padded(dest: &mut [u8], source: &[u8], offset: UInt256) {
+    let start = usize::try_from(offset).map_or(source.len(), |start| start.min(source.len()));
+    let available = &source[start..];
+    let count = available.len().min(dest.len());
+    dest[..count].copy_from_slice(&available[..count]);
+    dest[count..].fill(0);
 }
 
 impl Memory {
-    fn ensure_size(&mut self, end: usize) {
-        if end > self.bytes.len() {
-            self.bytes.resize(end, 0);
+    // Grows memory to cover `length` bytes at `offset`, rounded up to a
+    // whole number of words, and charges for the growth. Zero-length
+    // accesses never expand, whatever their offset.
+    fn expand(
+        &mut self,
+        gas: &mut Gas,
+        offset: UInt256,
+        length: UInt256,
+    ) -> Result<Range<usize>, VMError> {
+        if length == UInt256::ZERO {
+            return Ok(0..0);
+        }
+        // Nothing that overflows a u64 could ever be paid for.
+        let start = u64::try_from(offset).map_err(|_| VMError::OutOfGas)?;
+        let length = u64::try_from(length).map_err(|_| VMError::OutOfGas)?;
+        let end = start.checked_add(length).ok_or(VMError::OutOfGas)?;
+        let words = end.div_ceil(32);
+        let current_words = word_count(self.bytes.len());
+        if words > current_words {
+            gas.consume(memory_cost(words)? - memory_cost(current_words)?)?;
+            self.bytes.resize(words as usize * 32, 0);
         }
+        Ok(start as usize..end as usize)
     }
-    fn load(&self, offset: UInt256) -> Result<UInt256, VMError> {
-        let index: usize = offset.try_into().map_err(|_| VMError::BadAccess)?;
-        let end = index + 32;
-        Ok(UInt256::from_be_slice(&self.bytes[index..end]))
+
+    fn load(&mut self, gas: &mut Gas, offset: UInt256) -> Result<UInt256, VMError> {
+        let range = self.expand(gas, offset, UInt256::from(32u8))?;
+        Ok(UInt256::from_be_slice(&self.bytes[range]))
     }
 
-    fn store(&mut self, offset: UInt256, value: UInt256) -> Result<(), VMError> {
-        let index: usize = offset.try_into().map_err(|_| VMError::BadAccess)?;
-        let end = index + 32;
-        self.ensure_size(end);
-        value.to_be_bytes(&mut self.bytes[index..end]);
+    fn store(&mut self, gas: &mut Gas, offset: UInt256, value: UInt256) -> Result<(), VMError> {
+        let range = self.expand(gas, offset, UInt256::from(32u8))?;
+        value.to_be_bytes(&mut self.bytes[range]);
         Ok(())
     }
 
-    fn copy_out(&self, range: Range<UInt256>) -> Result<Vec<u8>, VMError> {
-        let usize_range = to_usize_range(range)?;
-        let mut out = vec![0u8; usize_range.end - usize_range.start];
-        out.copy_from_slice(&self.bytes[usize_range]);
-        Ok(out)
+    fn copy_out(
+        &mut self,
+        gas: &mut Gas,
+        offset: UInt256,
+        length: UInt256,
+    ) -> Result<Vec<u8>, VMError> {
+        let range = self.expand(gas, offset, length)?;
+        Ok(self.bytes[range].to_vec())
+    }
+
+    fn size(&self) -> usize {
+        self.bytes.len()
     }
 }
 
@@ -156,19 +363,241 @@ enum TaskResult {
 
 impl Task<'_> {
     fn jump_to(&mut self, new_pc: UInt256) -> Result<(), VMError> {
-        let from = self.input.index;
-        self.input.index = new_pc.try_into().map_err(|_| VMError::TypeConversion)?;
-        println!("Jumped from {:02X} to {:02X}", from, self.input.index);
+        // The jump instruction itself has already been consumed.
+        let from = self.frame.input.index - 1;
+        let to = usize::try_from(new_pc)
+            .ok()
+            .filter(|&to| self.frame.input.is_jump_dest(to))
+            .ok_or(VMError::InvalidJump { from, to: new_pc })?;
+        self.frame.input.index = to;
+        println!("Jumped from {:02X} to {:02X}", from, to);
         Ok(())
     }
 
+    // CALL, CALLCODE, DELEGATECALL and STATICCALL differ in whose storage
+    // context the callee's code runs in, who it sees as its caller and
+    // whether value moves:
+    //   CALL:         target's context, caller is us, value moves to target.
+    //   CALLCODE:     our context, caller is us, value "moves" to ourselves.
+    //   DELEGATECALL: our context, our caller and our value, nothing moves.
+    //   STATICCALL:   like CALL with no value, and nothing may be written.
+    fn call(&mut self, instruction: &Instruction) -> Result<(), VMError> {
+        let frame = &mut self.frame;
+        let gas = frame.stack.pop()?;
+        let target = Address::from(frame.stack.pop()?);
+        let value = match *instruction {
+            OP_CALL | OP_CALLCODE => frame.stack.pop()?,
+            _ => UInt256::ZERO,
+        };
+        let args_offset = frame.stack.pop()?;
+        let args_length = frame.stack.pop()?;
+        let return_offset = frame.stack.pop()?;
+        let return_length = frame.stack.pop()?;
+        let transfers_value = value != UInt256::ZERO;
+        if frame.is_static && transfers_value && *instruction == OP_CALL {
+            return Err(VMError::WriteProtection);
+        }
+        charge_account_access(self.state, &mut frame.gas, target)?;
+        let args = frame
+            .memory
+            .expand(&mut frame.gas, args_offset, args_length)?;
+        let return_range = frame
+            .memory
+            .expand(&mut frame.gas, return_offset, return_length)?;
+        if transfers_value {
+            frame.gas.consume(G_CALLVALUE)?;
+            if *instruction == OP_CALL && self.state.is_empty(target) {
+                frame.gas.consume(G_NEWACCOUNT)?;
+            }
+        }
+        // EIP-150: the callee gets at most all but one 64th of what is left,
+        // plus a stipend when value is sent.
+        let available = frame.gas.remaining - frame.gas.remaining / 64;
+        let forwarded = u64::try_from(gas).map_or(available, |gas| gas.min(available));
+        frame.gas.consume(forwarded)?;
+        let gas_limit = forwarded + if transfers_value { G_CALLSTIPEND } else { 0 };
+        let data = frame.memory.bytes[args].to_vec();
+        let here = &frame.message;
+        let message = match *instruction {
+            OP_CALL | OP_STATICCALL => Message {
+                value,
+                caller: here.address,
+                address: target,
+                gas_limit,
+                data,
+            },
+            OP_CALLCODE => Message {
+                value,
+                caller: here.address,
+                address: here.address,
+                gas_limit,
+                data,
+            },
+            _ => Message {
+                value: here.value,
+                caller: here.caller,
+                address: here.address,
+                gas_limit,
+                data,
+            },
+        };
+        println!(
+            "{}: {} -> {} ({} gas)",
+            instruction.name, message.caller, target, gas_limit
+        );
+
+        // Running out of depth or funds fails the call without running it.
+        let payer = here.address;
+        if self.callers.len() >= CALL_DEPTH_LIMIT || self.state.balance(payer) < value {
+            frame.gas.refund(gas_limit);
+            frame.return_data.clear();
+            return frame.stack.push(UInt256::ZERO);
+        }
+        let checkpoint = self.state.checkpoint();
+        if *instruction == OP_CALL {
+            self.state.transfer(payer, target, value);
+        }
+        let is_static = frame.is_static || *instruction == OP_STATICCALL;
+        let code = InputManager::from_bytes(self.state.code(target).to_vec());
+        let callee = Frame::new(code, message, is_static);
+        self.enter(callee, FrameKind::Call { return_range }, checkpoint);
+        Ok(())
+    }
+
+    // CREATE and CREATE2 run init code as a fresh account and deploy its
+    // output there. They differ only in how that address is derived.
+    fn create(&mut self, instruction: &Instruction) -> Result<(), VMError> {
+        let frame = &mut self.frame;
+        let value = frame.stack.pop()?;
+        let offset = frame.stack.pop()?;
+        let length = frame.stack.pop()?;
+        let salt = match *instruction {
+            OP_CREATE2 => Some(frame.stack.pop()?),
+            _ => None,
+        };
+        let range = frame.memory.expand(&mut frame.gas, offset, length)?;
+        // EIP-3860 limits and meters init code; CREATE2 also hashes it.
+        if range.len() > MAX_INITCODE_SIZE {
+            return Err(VMError::CodeSizeExceeded);
+        }
+        let words = word_count(range.len());
+        frame.gas.consume(G_INITCODEWORD * words)?;
+        if salt.is_some() {
+            frame.gas.consume(G_SHA3WORD * words)?;
+        }
+        let init_code = frame.memory.bytes[range].to_vec();
+        let creator = frame.message.address;
+        let gas_limit = frame.gas.remaining - frame.gas.remaining / 64;
+        frame.gas.consume(gas_limit)?;
+        frame.return_data.clear();
+
+        let nonce = self.state.nonce(creator);
+        if self.callers.len() >= CALL_DEPTH_LIMIT
+            || self.state.balance(creator) < value
+            || nonce == u64::MAX
+        {
+            frame.gas.refund(gas_limit);
+            return frame.stack.push(UInt256::ZERO);
+        }
+        self.state.increment_nonce(creator);
+        let address = match salt {
+            Some(salt) => create2_address(creator, salt, &init_code),
+            None => create_address(creator, nonce),
+        };
+        println!("{}: {} -> {}", instruction.name, creator, address);
+        self.state.warm_account(address);
+        // Deploying over an existing contract fails and burns the gas.
+        if is_occupied(self.state, address) {
+            return frame.stack.push(UInt256::ZERO);
+        }
+        let checkpoint = self.state.checkpoint();
+        self.state.create_contract(address);
+        self.state.transfer(creator, address, value);
+        let message = Message {
+            value,
+            caller: creator,
+            address,
+            gas_limit,
+            data: Vec::new(),
+        };
+        let callee = Frame::new(InputManager::from_bytes(init_code), message, false);
+        self.enter(callee, FrameKind::Create { address }, checkpoint);
+        Ok(())
+    }
+
+    // Suspends the current frame and starts running `callee`, whose
+    // changes to the world state began at `checkpoint`.
+    fn enter(&mut self, callee: Frame, kind: FrameKind, checkpoint: Checkpoint) {
+        self.callers.push(SuspendedFrame {
+            frame: mem::replace(&mut self.frame, callee),
+            kind,
+            checkpoint,
+        });
+    }
+
+    // Resumes the innermost caller with the outcome of the frame that
+    // just finished.
+    fn return_to_caller(&mut self, outcome: Result<InstructionResult, VMError>) {
+        let caller = self.callers.pop().expect("a callee has a caller");
+        let mut callee = mem::replace(&mut self.frame, caller.frame);
+        let (mut success, mut output) = match outcome {
+            Ok(InstructionResult::Return(data)) => (true, data),
+            Ok(InstructionResult::Stop) | Ok(InstructionResult::Continue) => (true, Vec::new()),
+            Ok(InstructionResult::Revert(data)) => (false, data),
+            // Exceptional halts burn all of the callee's gas.
+            Err(error) => {
+                println!("Call failed: {:?}", error);
+                callee.gas.remaining = 0;
+                (false, Vec::new())
+            }
+        };
+        if let (true, FrameKind::Create { address }) = (success, &caller.kind) {
+            let code = mem::take(&mut output);
+            if let Err(error) = deposit_code(self.state, &mut callee.gas, *address, code) {
+                println!("Deployment failed: {:?}", error);
+                callee.gas.remaining = 0;
+                success = false;
+            }
+        }
+        println!(
+            "Returned to {} (success: {})",
+            self.frame.message.address, success
+        );
+        self.frame.gas.refund(callee.gas.remaining);
+        if success {
+            self.state.commit();
+        } else {
+            self.state.revert(caller.checkpoint);
+        }
+        let result = match caller.kind {
+            FrameKind::Call { return_range } => {
+                let count = output.len().min(return_range.len());
+                let start = return_range.start;
+                self.frame.memory.bytes[start..start + count].copy_from_slice(&output[..count]);
+                UInt256::from_bool(success)
+            }
+            FrameKind::Create { address } if success => UInt256::from(address),
+            FrameKind::Create { .. } => UInt256::ZERO,
+        };
+        // A successful create leaves no return data, only its address.
+        self.frame.return_data = output;
+        self.frame
+            .stack
+            .push(result)
+            .expect("the call popped its arguments, so its result fits");
+    }
+
     fn execute_single_instruction(
         &mut self,
         instruction: &Instruction,
         arg_option: Option<UInt256>,
     ) -> Result<InstructionResult, VMError> {
-        let stack = &mut self.stack;
+        let stack = &mut self.frame.stack;
         print_instruction(instruction, arg_option);
+        // EIP-214: nothing may change state inside a STATICCALL.
+        if self.frame.is_static && modifies_state(instruction) {
+            return Err(VMError::WriteProtection);
+        }
         match *instruction {
             OP_STOP => {
                 return Ok(InstructionResult::Stop);
@@ -178,148 +607,499 @@ impl Task<'_> {
                 let b = stack.pop()?;
                 let result = a + b;
                 println!("ADD: {} + {} -> {}", a, b, result);
-                stack.push(result);
+                stack.push(result)?;
             }
             OP_SUB => {
                 let a = stack.pop()?;
                 let b = stack.pop()?;
                 let result = a - b;
                 println!("SUB: {} - {} -> {}", a, b, result);
-                stack.push(result);
+                stack.push(result)?;
+            }
+            OP_MUL => {
+                let a = stack.pop()?;
+                let b = stack.pop()?;
+                let result = a * b;
+                println!("MUL: {} * {} -> {}", a, b, result);
+                stack.push(result)?;
+            }
+            OP_DIV => {
+                let a = stack.pop()?;
+                let b = stack.pop()?;
+                let result = a / b;
+                println!("DIV: {} / {} -> {}", a, b, result);
+                stack.push(result)?;
+            }
+            OP_MOD => {
+                let a = stack.pop()?;
+                let b = stack.pop()?;
+                let result = a % b;
+                println!("MOD: {} % {} -> {}", a, b, result);
+                stack.push(result)?;
+            }
+            OP_SDIV => {
+                let a = stack.pop()?;
+                let b = stack.pop()?;
+                let result = a.signed_div(b);
+                println!("SDIV: {} / {} -> {}", a, b, result);
+                stack.push(result)?;
+            }
+            OP_SMOD => {
+                let a = stack.pop()?;
+                let b = stack.pop()?;
+                let result = a.signed_rem(b);
+                println!("SMOD: {} % {} -> {}", a, b, result);
+                stack.push(result)?;
+            }
+            OP_ADDMOD => {
+                let a = stack.pop()?;
+                let b = stack.pop()?;
+                let modulus = stack.pop()?;
+                let result = a.add_mod(b, modulus);
+                println!("ADDMOD: ({} + {}) % {} -> {}", a, b, modulus, result);
+                stack.push(result)?;
+            }
+            OP_MULMOD => {
+                let a = stack.pop()?;
+                let b = stack.pop()?;
+                let modulus = stack.pop()?;
+                let result = a.mul_mod(b, modulus);
+                println!("MULMOD: ({} * {}) % {} -> {}", a, b, modulus, result);
+                stack.push(result)?;
+            }
+            OP_EXP => {
+                let base = stack.pop()?;
+                let exponent = stack.pop()?;
+                let exponent_bytes = (256 - exponent.leading_zeros() as u64).div_ceil(8);
+                self.frame.gas.consume(G_EXPBYTE * exponent_bytes)?;
+                let result = base.pow(exponent);
+                println!("EXP: {} ** {} -> {}", base, exponent, result);
+                stack.push(result)?;
+            }
+            OP_SIGNEXTEND => {
+                let byte_index = stack.pop()?;
+                let value = stack.pop()?;
+                let result = value.sign_extend(byte_index);
+                println!("SIGNEXTEND: {} ({}) -> {}", value, byte_index, result);
+                stack.push(result)?;
             }
             OP_LT => {
                 let a = stack.pop()?;
                 let b = stack.pop()?;
                 let result = a < b;
                 println!("LT: {} < {} -> {}", a, b, result);
-                stack.push(UInt256::from_bool(result));
+                stack.push(UInt256::from_bool(result))?;
             }
             OP_GT => {
                 let a = stack.pop()?;
                 let b = stack.pop()?;
                 let result = a > b;
                 println!("GT: {} > {} -> {}", a, b, result);
-                stack.push(UInt256::from_bool(result));
+                stack.push(UInt256::from_bool(result))?;
+            }
+            OP_SLT => {
+                let a = stack.pop()?;
+                let b = stack.pop()?;
+                let result = a.signed_cmp(b) == Ordering::Less;
+                println!("SLT: {} < {} -> {}", a, b, result);
+                stack.push(UInt256::from_bool(result))?;
+            }
+            OP_SGT => {
+                let a = stack.pop()?;
+                let b = stack.pop()?;
+                let result = a.signed_cmp(b) == Ordering::Greater;
+                println!("SGT: {} > {} -> {}", a, b, result);
+                stack.push(UInt256::from_bool(result))?;
             }
             OP_EQ => {
                 let a = stack.pop()?;
                 let b = stack.pop()?;
                 let result = a == b;
                 println!("EQ: {} == {} -> {}", a, b, result);
-                stack.push(UInt256::from_bool(result));
+                stack.push(UInt256::from_bool(result))?;
+            }
+            OP_AND => {
+                let a = stack.pop()?;
+                let b = stack.pop()?;
+                let result = a & b;
+                println!("AND: {} & {} -> {}", a, b, result);
+                stack.push(result)?;
+            }
+            OP_OR => {
+                let a = stack.pop()?;
+                let b = stack.pop()?;
+                let result = a | b;
+                println!("OR: {} | {} -> {}", a, b, result);
+                stack.push(result)?;
+            }
+            OP_XOR => {
+                let a = stack.pop()?;
+                let b = stack.pop()?;
+                let result = a ^ b;
+                println!("XOR: {} ^ {} -> {}", a, b, result);
+                stack.push(result)?;
             }
             OP_NOT => {
                 let a = stack.pop()?;
                 let result = !a;
                 println!("NOT: !{} -> {}", a, result);
-                stack.push(result);
+                stack.push(result)?;
+            }
+            OP_BYTE => {
+                let index = stack.pop()?;
+                let value = stack.pop()?;
+                // Indices too large for usize are past the end of the word.
+                let byte = usize::try_from(index).map_or(0, |index| value.byte(index));
+                println!("BYTE: {}[{}] -> {:02X}", value, index, byte);
+                stack.push(UInt256::from_u128(byte as u128))?;
             }
             OP_SHL => {
                 let shift = stack.pop()?;
                 let value = stack.pop()?;
                 let result = value << shift;
                 println!("SHL: {} << {} -> {}", value, shift, result);
-                stack.push(result);
+                stack.push(result)?;
             }
             OP_SHR => {
                 let shift = stack.pop()?;
                 let value = stack.pop()?;
                 let result = value >> shift;
                 println!("SHR: {} >> {} -> {}", value, shift, result);
-                stack.push(result);
+                stack.push(result)?;
+            }
+            OP_SAR => {
+                let shift = stack.pop()?;
+                let value = stack.pop()?;
+                let result = value.sar(shift);
+                println!("SAR: {} >> {} -> {}", value, shift, result);
+                stack.push(result)?;
+            }
+            OP_SHA3 => {
+                let offset = stack.pop()?;
+                let length = stack.pop()?;
+                let range = self
+                    .frame
+                    .memory
+                    .expand(&mut self.frame.gas, offset, length)?;
+                self.frame
+                    .gas
+                    .consume(G_SHA3WORD * word_count(range.len()))?;
+                let hash = UInt256::from(keccak256(&self.frame.memory.bytes[range]));
+                println!("SHA3: {}..{} -> {}", offset, offset + length, hash);
+                stack.push(hash)?;
             }
             OP_MLOAD => {
                 let offset = stack.pop()?;
-                let value = self.memory.load(offset)?;
+                let value = self.frame.memory.load(&mut self.frame.gas, offset)?;
                 println!("MLOAD: {} -> {}", offset, value);
-                stack.push(value);
+                stack.push(value)?;
             }
             OP_MSTORE => {
                 let offset = stack.pop()?;
                 let value = stack.pop()?;
-                self.memory.store(offset, value)?;
+                self.frame
+                    .memory
+                    .store(&mut self.frame.gas, offset, value)?;
+            }
+            OP_MSTORE8 => {
+                let offset = stack.pop()?;
+                let value = stack.pop()?;
+                let range = self
+                    .frame
+                    .memory
+                    .expand(&mut self.frame.gas, offset, UInt256::ONE)?;
+                // Only the least significant byte is stored.
+                self.frame.memory.bytes[range.start] = value.byte(31);
+            }
+            OP_PC => {
+                // The PC instruction itself has already been consumed.
+                let pc = self.frame.input.index - 1;
+                println!("PC: -> {:02X}", pc);
+                stack.push(UInt256::try_from(pc).map_err(|_| VMError::OutOfBounds)?)?;
+            }
+            OP_MCOPY => {
+                let dest_offset = stack.pop()?;
+                let offset = stack.pop()?;
+                let length = stack.pop()?;
+                // Memory must cover both ranges; expansion is charged on
+                // the final size, so the order doesn't matter.
+                let from = self
+                    .frame
+                    .memory
+                    .expand(&mut self.frame.gas, offset, length)?;
+                let to = self
+                    .frame
+                    .memory
+                    .expand(&mut self.frame.gas, dest_offset, length)?;
+                self.frame.gas.consume(G_COPY * word_count(to.len()))?;
+                println!("MCOPY from {:?} to {:?}", from, to);
+                self.frame.memory.bytes.copy_within(from, to.start);
+            }
+            OP_MSIZE => {
+                let size = self.frame.memory.size();
+                println!("MSIZE: -> {}", size);
+                stack.push(UInt256::try_from(size).map_err(|_| VMError::OutOfBounds)?)?;
+            }
+            OP_ADDRESS => {
+                println!("ADDRESS: -> {}", self.frame.message.address);
+                stack.push(UInt256::from(self.frame.message.address))?;
+            }
+            OP_ORIGIN => {
+                println!("ORIGIN: -> {}", self.env.tx.origin);
+                stack.push(UInt256::from(self.env.tx.origin))?;
+            }
+            OP_CALLER => {
+                println!("CALLER: -> {}", self.frame.message.caller);
+                stack.push(UInt256::from(self.frame.message.caller))?;
+            }
+            OP_GASPRICE => {
+                println!("GASPRICE: -> {}", self.env.tx.gas_price);
+                stack.push(self.env.tx.gas_price)?;
+            }
+            OP_COINBASE => {
+                println!("COINBASE: -> {}", self.env.block.coinbase);
+                stack.push(UInt256::from(self.env.block.coinbase))?;
+            }
+            OP_TIMESTAMP => {
+                println!("TIMESTAMP: -> {}", self.env.block.timestamp);
+                stack.push(UInt256::from(self.env.block.timestamp))?;
+            }
+            OP_NUMBER => {
+                println!("NUMBER: -> {}", self.env.block.number);
+                stack.push(UInt256::from(self.env.block.number))?;
+            }
+            OP_DIFFICULTY => {
+                // Since the merge this opcode returns PREVRANDAO instead.
+                println!("PREVRANDAO: -> {}", self.env.block.prevrandao);
+                stack.push(self.env.block.prevrandao)?;
+            }
+            OP_GASLIMIT => {
+                println!("GASLIMIT: -> {}", self.env.block.gas_limit);
+                stack.push(UInt256::from(self.env.block.gas_limit))?;
+            }
+            OP_CHAINID => {
+                println!("CHAINID: -> {}", self.env.block.chain_id);
+                stack.push(UInt256::from(self.env.block.chain_id))?;
+            }
+            OP_BASEFEE => {
+                println!("BASEFEE: -> {}", self.env.block.base_fee);
+                stack.push(self.env.block.base_fee)?;
+            }
+            OP_BLOBHASH => {
+                let index = stack.pop()?;
+                // Indices past the transaction's blobs read as zero.
+                let blob_hashes = &self.env.tx.blob_hashes;
+                let hash = usize::try_from(index)
+                    .ok()
+                    .and_then(|index| blob_hashes.get(index))
+                    .copied()
+                    .unwrap_or(UInt256::ZERO);
+                println!("BLOBHASH: {} -> {}", index, hash);
+                stack.push(hash)?;
+            }
+            OP_BLOBBASEFEE => {
+                println!("BLOBBASEFEE: -> {}", self.env.block.blob_base_fee);
+                stack.push(self.env.block.blob_base_fee)?;
             }
             OP_CALLVALUE => {
-                println!("CALLVALUE: -> {}", self.message.value);
-                stack.push(self.message.value);
+                println!(
+                    "CALLVALUE: -> {} ({} ether)",
+                    self.frame.message.value,
+                    format_units(self.frame.message.value, Unit::Ether)
+                );
+                stack.push(self.frame.message.value)?;
             }
             OP_CALLDATASIZE => {
-                let size = self.message.data.len();
+                let size = self.frame.message.data.len();
                 println!("CALLDATASIZE: -> {}", size);
-                stack.push(size.try_into().map_err(|_| VMError::OutOfBounds)?);
+                stack.push(size.try_into().map_err(|_| VMError::OutOfBounds)?)?;
             }
             OP_CALLDATALOAD => {
                 let offset = stack.pop()?;
-                let index: usize = offset.try_into().map_err(|_| VMError::BadAccess)?;
-                let end: usize = index + 32;
-                let slice = self
-                    .message
-                    .data
-                    .get(index..end)
-                    .ok_or(VMError::BadAccess)?;
-                let word = UInt256::from_be_slice(slice);
+                let mut bytes = [0u8; 32];
+                copy_padded(&mut bytes, &self.frame.message.data, offset);
+                let word = UInt256::from(bytes);
                 println!("CALLDATALOAD: {} -> {}", offset, word);
-                self.stack.push(word);
+                stack.push(word)?;
             }
-            OP_DUP1 => {
-                let value = stack.peek(0)?;
-                println!("DUP1: -> {}", value);
-                stack.push(value);
-            }
-            OP_DUP2 => {
-                let value = stack.peek(1)?;
-                println!("DUP2: -> {}", value);
-                stack.push(value);
-            }
-            OP_DUP3 => {
-                let value = stack.peek(2)?;
-                println!("DUP3: -> {}", value);
-                stack.push(value);
-            }
-            OP_SWAP1 => {
-                stack.swap(0, 1)?;
-                println!("SWAP1 (old: {} new: {})", stack.peek(1)?, stack.peek(0)?);
+            OP_CALLDATACOPY => {
+                let dest_offset = stack.pop()?;
+                let offset = stack.pop()?;
+                let length = stack.pop()?;
+                let to = self
+                    .frame
+                    .memory
+                    .expand(&mut self.frame.gas, dest_offset, length)?;
+                self.frame.gas.consume(G_COPY * word_count(to.len()))?;
+                println!("CALLDATACOPY from {} to {:?}", offset, to);
+                copy_padded(
+                    &mut self.frame.memory.bytes[to],
+                    &self.frame.message.data,
+                    offset,
+                );
             }
-            OP_SWAP2 => {
-                stack.swap(0, 2)?;
-                println!("SWAP2 (old: {} new: {})", stack.peek(2)?, stack.peek(0)?);
+            OP_CODESIZE => {
+                let size = self.frame.input.ops.len();
+                println!("CODESIZE: -> {}", size);
+                stack.push(UInt256::try_from(size).map_err(|_| VMError::OutOfBounds)?)?;
             }
             OP_ISZERO => {
                 let a = stack.pop()?;
                 println!("ISZERO -> {}", a == UInt256::ZERO);
                 if a == UInt256::ZERO {
-                    stack.push(UInt256::ONE)
+                    stack.push(UInt256::ONE)?;
                 } else {
-                    stack.push(UInt256::ZERO)
+                    stack.push(UInt256::ZERO)?;
                 }
             }
             OP_CODECOPY => {
                 let dest_offset = stack.pop()?;
                 let offset = stack.pop()?;
                 let length = stack.pop()?;
-                // 	memory[destOffset:destOffset+length] = address(this).code[offset: offset + length]
-                let from = to_usize_range(offset..offset + length)?;
-                let to = to_usize_range(dest_offset..dest_offset + length)?;
-                println!("CODECOPY from {:?} to {:?}", from, to);
-                // TODO: Does this index from input[0] or $PC or something else?
-                self.memory.ensure_size(to.end);
-                self.memory.bytes[to].copy_from_slice(&self.input.ops[from]);
+                // memory[dest_offset..dest_offset + length] = code[offset..offset + length]
+                let to = self
+                    .frame
+                    .memory
+                    .expand(&mut self.frame.gas, dest_offset, length)?;
+                self.frame.gas.consume(G_COPY * word_count(to.len()))?;
+                println!("CODECOPY from {} to {:?}", offset, to);
+                copy_padded(
+                    &mut self.frame.memory.bytes[to],
+                    &self.frame.input.ops,
+                    offset,
+                );
             }
             OP_SLOAD => {
                 let key = stack.pop()?;
-                let value = self
-                    .storage
-                    .load(key)
-                    .map_err(|e| VMError::StorageError(e))?;
+                let address = self.frame.message.address;
+                if self.state.warm_storage(address, key) {
+                    self.frame.gas.consume(G_COLDSLOAD - G_WARMACCESS)?;
+                }
+                let value = self.state.storage(address, key);
                 println!("SLOAD: {} -> {}", key, value);
-                stack.push(value);
+                stack.push(value)?;
             }
             OP_SSTORE => {
                 let key = stack.pop()?;
                 let value = stack.pop()?;
-                self.storage
-                    .store(key, value)
-                    .map_err(|e| VMError::StorageError(e))?;
+                let address = self.frame.message.address;
+                // EIP-2200: a frame left with only a call stipend can't write.
+                if self.frame.gas.remaining <= G_CALLSTIPEND {
+                    return Err(VMError::OutOfGas);
+                }
+                if self.state.warm_storage(address, key) {
+                    self.frame.gas.consume(G_COLDSLOAD)?;
+                }
+                // Only the first write to a slot in a transaction is charged
+                // in full. Undoing earlier writes earns a refund.
+                let original = self.state.original_storage(address, key);
+                let current = self.state.storage(address, key);
+                let zero = UInt256::ZERO;
+                let cost = if current == value || original != current {
+                    G_WARMACCESS
+                } else if original == zero {
+                    G_SSET
+                } else {
+                    G_SRESET
+                };
+                self.frame.gas.consume(cost)?;
+                if current != value && original != zero {
+                    if current == zero {
+                        self.state.remove_refund(R_SCLEAR);
+                    } else if value == zero {
+                        self.state.add_refund(R_SCLEAR);
+                    }
+                }
+                if current != value && original != current && original == value {
+                    let first_write = if original == zero { G_SSET } else { G_SRESET };
+                    self.state.add_refund(first_write - G_WARMACCESS);
+                }
+                println!("SSTORE: {} -> {}", key, value);
+                self.state.set_storage(address, key, value);
+            }
+            OP_TLOAD => {
+                let key = stack.pop()?;
+                let value = self
+                    .state
+                    .transient_storage(self.frame.message.address, key);
+                println!("TLOAD: {} -> {}", key, value);
+                stack.push(value)?;
+            }
+            OP_TSTORE => {
+                let key = stack.pop()?;
+                let value = stack.pop()?;
+                println!("TSTORE: {} -> {}", key, value);
+                self.state
+                    .set_transient_storage(self.frame.message.address, key, value);
+            }
+            OP_BALANCE => {
+                let address = Address::from(stack.pop()?);
+                charge_account_access(self.state, &mut self.frame.gas, address)?;
+                let balance = self.state.balance(address);
+                println!("BALANCE: {} -> {}", address, balance);
+                stack.push(balance)?;
+            }
+            OP_SELFBALANCE => {
+                let balance = self.state.balance(self.frame.message.address);
+                println!("SELFBALANCE: -> {}", balance);
+                stack.push(balance)?;
+            }
+            OP_EXTCODESIZE => {
+                let address = Address::from(stack.pop()?);
+                charge_account_access(self.state, &mut self.frame.gas, address)?;
+                let size = self.state.code(address).len();
+                println!("EXTCODESIZE: {} -> {}", address, size);
+                stack.push(UInt256::try_from(size).map_err(|_| VMError::OutOfBounds)?)?;
+            }
+            OP_EXTCODECOPY => {
+                let address = Address::from(stack.pop()?);
+                let dest_offset = stack.pop()?;
+                let offset = stack.pop()?;
+                let length = stack.pop()?;
+                charge_account_access(self.state, &mut self.frame.gas, address)?;
+                let to = self
+                    .frame
+                    .memory
+                    .expand(&mut self.frame.gas, dest_offset, length)?;
+                self.frame.gas.consume(G_COPY * word_count(to.len()))?;
+                println!("EXTCODECOPY {} from {} to {:?}", address, offset, to);
+                copy_padded(
+                    &mut self.frame.memory.bytes[to],
+                    self.state.code(address),
+                    offset,
+                );
+            }
+            OP_EXTCODEHASH => {
+                let address = Address::from(stack.pop()?);
+                charge_account_access(self.state, &mut self.frame.gas, address)?;
+                let hash = self.state.code_hash(address);
+                println!("EXTCODEHASH: {} -> {}", address, hash);
+                stack.push(hash)?;
+            }
+            OP_RETURNDATASIZE => {
+                let size = self.frame.return_data.len();
+                println!("RETURNDATASIZE: -> {}", size);
+                stack.push(UInt256::try_from(size).map_err(|_| VMError::OutOfBounds)?)?;
+            }
+            OP_RETURNDATACOPY => {
+                let dest_offset = stack.pop()?;
+                let offset = stack.pop()?;
+                let length = stack.pop()?;
+                // Unlike the other copies, reading past the end of the
+                // return data halts instead of padding with zeros.
+                let end = offset
+                    .checked_add(length)
+                    .and_then(|end| usize::try_from(end).ok())
+                    .filter(|&end| end <= self.frame.return_data.len())
+                    .ok_or(VMError::OutOfBounds)?;
+                let to = self
+                    .frame
+                    .memory
+                    .expand(&mut self.frame.gas, dest_offset, length)?;
+                self.frame.gas.consume(G_COPY * word_count(to.len()))?;
+                println!("RETURNDATACOPY from {} to {:?}", offset, to);
+                let from = end - to.len()..end;
+                self.frame.memory.bytes[to].copy_from_slice(&self.frame.return_data[from]);
             }
             OP_JUMP => {
                 let destination = stack.pop()?;
@@ -338,6 +1118,11 @@ impl Task<'_> {
                     self.jump_to(destination)?;
                 }
             }
+            OP_GAS => {
+                // The GAS instruction's own cost has already been charged.
+                println!("GAS: -> {}", self.frame.gas.remaining);
+                stack.push(UInt256::from(self.frame.gas.remaining))?;
+            }
             OP_JUMPDEST => {
                 // Metadata to annotate possible jump destination, no action.
             }
@@ -347,17 +1132,33 @@ impl Task<'_> {
             OP_RETURN => {
                 let offset = stack.pop()?;
                 let length = stack.pop()?;
-                let range = offset..offset + length;
                 println!("RETURN {}..{}", offset, offset + length);
-                return Ok(InstructionResult::Return(self.memory.copy_out(range)?));
+                let data = self
+                    .frame
+                    .memory
+                    .copy_out(&mut self.frame.gas, offset, length)?;
+                return Ok(InstructionResult::Return(data));
             }
             OP_REVERT => {
                 // https://github.com/ethereum/EIPs/blob/master/EIPS/eip-140.md
-                // TODO: Should revert all actions?
+                // Its changes are undone by whoever took the checkpoint
+                // it runs under: the caller, or run_transaction.
                 let offset = stack.pop()?;
                 let length = stack.pop()?;
-                let range = offset..offset + length;
-                return Ok(InstructionResult::Revert(self.memory.copy_out(range)?));
+                let data = self
+                    .frame
+                    .memory
+                    .copy_out(&mut self.frame.gas, offset, length)?;
+                return Ok(InstructionResult::Revert(data));
+            }
+            OP_CALL | OP_CALLCODE | OP_DELEGATECALL | OP_STATICCALL => {
+                self.call(instruction)?;
+            }
+            OP_CREATE | OP_CREATE2 => {
+                self.create(instruction)?;
+            }
+            OP_PUSH0 => {
+                stack.push(UInt256::ZERO)?;
             }
             // All push instructions:
             Instruction {
@@ -365,14 +1166,87 @@ impl Task<'_> {
             } => {
                 let arg = arg_option.ok_or(VMError::BadArg)?;
                 // println!("{}: -> {}", instruction.name, arg);
-                stack.push(arg);
+                stack.push(arg)?;
+            }
+            // All dup instructions, DUP1 copies the top of the stack:
+            Instruction {
+                op: 0x80..=0x8F, ..
+            } => {
+                let depth = (instruction.op - 0x80) as usize;
+                let value = stack.peek(depth)?;
+                println!("DUP{}: -> {}", depth + 1, value);
+                stack.push(value)?;
+            }
+            // All swap instructions, SWAP1 exchanges the top two items:
+            Instruction {
+                op: 0x90..=0x9F, ..
+            } => {
+                let depth = (instruction.op - 0x8F) as usize;
+                stack.swap(0, depth)?;
+                println!(
+                    "SWAP{} (old: {} new: {})",
+                    depth,
+                    stack.peek(depth)?,
+                    stack.peek(0)?
+                );
+            }
+            // All log instructions, LOG0 has no topics:
+            Instruction {
+                op: 0xA0..=0xA4, ..
+            } => {
+                let topic_count = (instruction.op - 0xA0) as usize;
+                let offset = stack.pop()?;
+                let length = stack.pop()?;
+                let mut topics = Vec::with_capacity(topic_count);
+                for _ in 0..topic_count {
+                    topics.push(stack.pop()?);
+                }
+                let range = self
+                    .frame
+                    .memory
+                    .expand(&mut self.frame.gas, offset, length)?;
+                self.frame.gas.consume(G_LOGTOPIC * topic_count as u64)?;
+                self.frame.gas.consume(G_LOGDATA * range.len() as u64)?;
+                let log = Log {
+                    address: self.frame.message.address,
+                    topics,
+                    data: self.frame.memory.bytes[range].to_vec(),
+                };
+                println!("LOG{}: {:?}", topic_count, log);
+                self.state.add_log(log);
+            }
+            OP_SELFDESTRUCT => {
+                let beneficiary = Address::from(stack.pop()?);
+                let address = self.frame.message.address;
+                let balance = self.state.balance(address);
+                if self.state.warm_account(beneficiary) {
+                    self.frame.gas.consume(G_COLDACCOUNTACCESS)?;
+                }
+                if balance != UInt256::ZERO && self.state.is_empty(beneficiary) {
+                    self.frame.gas.consume(G_NEWACCOUNT)?;
+                }
+                println!("SELFDESTRUCT: {} -> {} ({})", address, beneficiary, balance);
+                self.state.self_destruct(address, beneficiary);
+                return Ok(InstructionResult::Stop);
             }
             _ => {
                 return Err(VMError::BadOp(instruction.op));
             }
         }
-        return Ok(InstructionResult::Continue);
+        Ok(InstructionResult::Continue)
+    }
+    fn step(&mut self, ops: &HashMap<u8, &Instruction>) -> Result<InstructionResult, VMError> {
+        // Running off the end of the code is an implicit STOP.
+        let op = match self.frame.input.take_op() {
+            Some(op) => op,
+            None => return Ok(InstructionResult::Stop),
+        };
+        let inst = ops.get(&op).ok_or(VMError::BadOp(op))?;
+        let arg_option = self.frame.input.take_arg(inst.arg);
+        self.frame.gas.consume(inst.gas)?;
+        self.execute_single_instruction(inst, arg_option)
     }
+
     fn execute(&mut self) -> Result<TaskResult, VMError> {
         // Does INSTRUCTIONS, take_op, inst and arg_option just
         // belong in some sort of Disasembler class?
@@ -382,30 +1256,29 @@ impl Task<'_> {
             .iter()
             .map(|instruction| (instruction.op, instruction))
             .collect();
-        let mut loop_breaker = 0;
-        while let Some(op) = self.input.take_op() {
-            let inst = ops.get(&op).ok_or(VMError::BadOp(op))?;
-            let arg_option = self.input.take_arg(inst.arg)?;
-            match self.execute_single_instruction(inst, arg_option)? {
+        loop {
+            let outcome = self.step(&ops);
+            if let Ok(InstructionResult::Continue) = outcome {
+                println!("{}", self.frame.stack);
+                continue;
+            }
+            // A callee's errors are the caller's failed call, not ours.
+            if !self.callers.is_empty() {
+                self.return_to_caller(outcome);
+                continue;
+            }
+            match outcome? {
                 InstructionResult::Revert(data) => {
                     return Ok(TaskResult::Revert(data));
                 }
                 InstructionResult::Return(data) => {
                     return Ok(TaskResult::Return(data));
                 }
-                InstructionResult::Continue => {
-                    println!("{}", self.stack);
-                    loop_breaker += 1;
-                    if loop_breaker > 100 {
-                        panic!();
-                    }
-                }
-                InstructionResult::Stop => {
+                InstructionResult::Stop | InstructionResult::Continue => {
                     return Ok(TaskResult::Stop);
                 }
             }
         }
-        Err(VMError::EndOfInstructions)
     }
 }
 
@@ -413,6 +1286,27 @@ impl Task<'_> {
 pub struct InputManager {
     ops: Vec<u8>,
     index: usize,
+    // One bit per byte of `ops`, set where a JUMPDEST opcode sits outside
+    // of any PUSH immediate.
+    jump_dests: Vec<u64>,
+}
+
+fn analyze_jump_dests(ops: &[u8]) -> Vec<u64> {
+    let mut bitmap = vec![0u64; ops.len().div_ceil(64)];
+    let mut index = 0;
+    while index < ops.len() {
+        let op = ops[index];
+        if op == OP_JUMPDEST.op {
+            bitmap[index / 64] |= 1 << (index % 64);
+        }
+        // PUSH1..PUSH32 are followed by 1..32 bytes of data, which may
+        // contain 0x5B without being a jump destination.
+        if (OP_PUSH1.op..=OP_PUSH32.op).contains(&op) {
+            index += (op - OP_PUSH1.op) as usize + 1;
+        }
+        index += 1;
+    }
+    bitmap
 }
 
 impl ArgType {
@@ -425,41 +1319,45 @@ impl ArgType {
 }
 
 impl InputManager {
-    pub fn from_string(contents: &String) -> InputManager {
+    pub fn from_string(contents: &str) -> InputManager {
         InputManager::from_bytes(hex_string_as_vec_u8(contents))
     }
 
     pub fn from_bytes(ops: Vec<u8>) -> InputManager {
-        InputManager { ops, index: 0 }
+        let jump_dests = analyze_jump_dests(&ops);
+        InputManager {
+            ops,
+            index: 0,
+            jump_dests,
+        }
     }
 
-    fn take_u8(&mut self) -> Result<u8, VMError> {
-        if self.index < self.ops.len() {
-            let value = self.ops[self.index];
-            self.index += 1;
-            Ok(value)
-        } else {
-            Err(VMError::BadArg)
-        }
+    fn is_jump_dest(&self, index: usize) -> bool {
+        self.jump_dests
+            .get(index / 64)
+            .is_some_and(|bits| bits & (1 << (index % 64)) != 0)
     }
 
-    fn take(&mut self, size: usize) -> Result<UInt256, VMError> {
-        let end = self.index + size;
-        if end <= self.ops.len() {
-            let bytes = &self.ops[self.index..end];
-            self.index += size;
-            Ok(UInt256::from_be_slice(bytes))
-        } else {
-            Err(VMError::BadArg)
+    fn take_op(&mut self) -> Option<u8> {
+        let op = self.ops.get(self.index).copied();
+        if op.is_some() {
+            self.index += 1;
         }
+        op
     }
 
-    fn take_op(&mut self) -> Option<u8> {
-        self.take_u8().ok()
+    // A push cut off by the end of the code reads the missing bytes as
+    // zeros, leaving nothing after it to run.
+    fn take(&mut self, size: usize) -> UInt256 {
+        let mut bytes = [0u8; 32];
+        let offset = UInt256::from(self.index as u64);
+        copy_padded(&mut bytes[..size], &self.ops, offset);
+        self.index = (self.index + size).min(self.ops.len());
+        UInt256::from_be_slice(&bytes[..size])
     }
 
-    fn take_arg(&mut self, arg_type: ArgType) -> Result<Option<UInt256>, VMError> {
-        arg_type.size().map(|size| self.take(size)).transpose()
+    fn take_arg(&mut self, arg_type: ArgType) -> Option<UInt256> {
+        arg_type.size().map(|size| self.take(size))
     }
 }
 
@@ -483,7 +1381,7 @@ pub fn dissemble(input: &mut InputManager) -> Result<(), VMError> {
 
     while let Some(op) = input.take_op() {
         let inst = ops.get(&op).ok_or(VMError::BadOp(op))?;
-        let arg_option = input.take_arg(inst.arg)?;
+        let arg_option = input.take_arg(inst.arg);
         print_instruction(inst, arg_option);
     }
 
@@ -492,77 +1390,976 @@ pub fn dissemble(input: &mut InputManager) -> Result<(), VMError> {
 
 #[derive(Clone, PartialEq, Eq)]
 pub enum ContractError {
-    Revert(Vec<u8>),
-    InternalError(VMError),
-    UnexpectedStop,
+    // A failed transaction still pays for the gas it used. Transactions
+    // rejected before any code runs use none.
+    Revert { data: Vec<u8>, gas_used: u64 },
+    InternalError { error: VMError, gas_used: u64 },
+    InsufficientBalance,
 }
 
 impl fmt::Debug for ContractError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            ContractError::Revert(data) => {
-                write!(f, "Revert({:02X?})", data)
+            ContractError::Revert { data, gas_used } => {
+                write!(f, "Revert({:02X?}, gas used: {})", data, gas_used)
             }
-            ContractError::InternalError(error) => {
-                write!(f, "InternalError({:?})", error)
+            ContractError::InternalError { error, gas_used } => {
+                write!(f, "InternalError({:?}, gas used: {})", error, gas_used)
             }
-            ContractError::UnexpectedStop => {
-                write!(f, "UnexpectedStop")
+            ContractError::InsufficientBalance => {
+                write!(f, "InsufficientBalance")
             }
         }
     }
 }
 
-pub fn send_message_to_contract(
+#[derive(Debug, PartialEq, Eq)]
+pub struct ExecutionResult {
+    pub return_data: Vec<u8>,
+    pub gas_used: u64,
+    // Events in the order they were emitted. A reverted call emits none.
+    pub logs: Vec<Log>,
+    // Where a create transaction deployed its contract.
+    pub contract_address: Option<Address>,
+}
+
+// Charged before any code runs: a flat fee plus a per-byte fee for the
+// call data, cheaper for zero bytes.
+fn intrinsic_gas(data: &[u8]) -> u64 {
+    data.iter().fold(G_TRANSACTION, |gas, &byte| {
+        gas + if byte == 0 {
+            G_TXDATAZERO
+        } else {
+            G_TXDATANONZERO
+        }
+    })
+}
+
+// Runs the top-level frame of a transaction. Unless it succeeds, the
+// world state is left as it was, bar the sender's nonce.
+fn run_transaction(
+    env: &Env,
+    state: &mut WorldState,
     message: Message,
-    wrapper: InputManager,
-) -> Result<(), ContractError> {
-    let mut task = Task::new(wrapper, &message);
-    let contract_bytes = match task
-        .execute()
-        .map_err(|e| ContractError::InternalError(e))?
-    {
-        TaskResult::Revert(data) => return Err(ContractError::Revert(data)),
-        TaskResult::Return(bytes) => bytes,
-        TaskResult::Stop => return Err(ContractError::UnexpectedStop),
-    };
-    println!("Got contract, executing!");
-    let contract = InputManager::from_bytes(contract_bytes);
-    let mut task = Task::new(contract, &message);
-    match task
-        .execute()
-        .map_err(|e| ContractError::InternalError(e))?
-    {
-        TaskResult::Revert(data) => return Err(ContractError::Revert(data)),
-        TaskResult::Return(data) => {
+    code: Vec<u8>,
+    intrinsic_gas: u64,
+    contract_address: Option<Address>,
+) -> Result<ExecutionResult, ContractError> {
+    if let Some(address) = contract_address {
+        if is_occupied(state, address) {
+            return Err(ContractError::InternalError {
+                error: VMError::AddressCollision,
+                gas_used: 0,
+            });
+        }
+    }
+    // EIP-2929 and EIP-3651: the accounts every transaction touches start
+    // out warm, as do the precompiles at 0x01 to 0x0a.
+    for address in [message.caller, message.address, env.block.coinbase].iter() {
+        state.warm_account(*address);
+    }
+    for precompile in 1..=0x0Au8 {
+        state.warm_account(Address::from(UInt256::from(precompile)));
+    }
+    let checkpoint = state.checkpoint();
+    if let Some(address) = contract_address {
+        state.create_contract(address);
+    }
+    if !state.transfer(message.caller, message.address, message.value) {
+        state.revert(checkpoint);
+        state.finish_transaction();
+        return Err(ContractError::InsufficientBalance);
+    }
+    let mut task = Task::new(InputManager::from_bytes(code), message, env, state);
+    let mut outcome = task
+        .frame
+        .gas
+        .consume(intrinsic_gas)
+        .and_then(|()| task.execute());
+    if let (Some(address), Ok(TaskResult::Return(code))) = (contract_address, &mut outcome) {
+        let code = mem::take(code);
+        if let Err(error) = deposit_code(task.state, &mut task.frame.gas, address, code) {
+            outcome = Err(error);
+        }
+    }
+    // Like a failed call, an exceptional halt burns all the gas left.
+    if outcome.is_err() {
+        task.frame.gas.remaining = 0;
+    }
+    let mut gas_used = task.frame.gas.used();
+    if let Ok(TaskResult::Return(_)) | Ok(TaskResult::Stop) = outcome {
+        state.commit();
+    } else {
+        state.revert(checkpoint);
+    }
+    // EIP-3529 caps the refund at a fifth of the gas used. Reverting took
+    // away any refund a failed transaction had earned.
+    gas_used -= state.refund().min(gas_used / 5);
+    let logs = state.finish_transaction();
+    let return_data = match outcome {
+        Ok(TaskResult::Return(data)) => {
             println!("return Data: {:02X?}", data);
-            return Ok(());
+            data
         }
-        TaskResult::Stop => return Ok(()),
+        Ok(TaskResult::Stop) => Vec::new(),
+        Ok(TaskResult::Revert(data)) => return Err(ContractError::Revert { data, gas_used }),
+        Err(error) => return Err(ContractError::InternalError { error, gas_used }),
+    };
+    Ok(ExecutionResult {
+        return_data,
+        gas_used,
+        logs,
+        contract_address,
+    })
+}
+
+/// Calls the contract at `message.address` with `message.data`.
+pub fn send_message_to_contract(
+    env: &Env,
+    state: &mut WorldState,
+    message: Message,
+) -> Result<ExecutionResult, ContractError> {
+    let code = state.code(message.address).to_vec();
+    let intrinsic_gas = intrinsic_gas(&message.data);
+    state.increment_nonce(message.caller);
+    run_transaction(env, state, message, code, intrinsic_gas, None)
+}
+
+/// Deploys a contract from `message.caller`, running `message.data` as its
+/// init code. The new address is derived from the sender's nonce and
+/// returned as the result's `contract_address`; `message.address` is
+/// ignored.
+pub fn create_contract(
+    env: &Env,
+    state: &mut WorldState,
+    mut message: Message,
+) -> Result<ExecutionResult, ContractError> {
+    let init_code = mem::take(&mut message.data);
+    if init_code.len() > MAX_INITCODE_SIZE {
+        return Err(ContractError::InternalError {
+            error: VMError::CodeSizeExceeded,
+            gas_used: 0,
+        });
     }
+    let intrinsic_gas =
+        intrinsic_gas(&init_code) + G_TXCREATE + G_INITCODEWORD * word_count(init_code.len());
+    let address = create_address(message.caller, state.nonce(message.caller));
+    state.increment_nonce(message.caller);
+    message.address = address;
+    println!("Deploying to {}", address);
+    run_transaction(env, state, message, init_code, intrinsic_gas, Some(address))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::abi::*;
+    use crate::env::*;
+    fn address_from(byte: u8) -> Address {
+        Address::from(UInt256::from(byte))
+    }
+    // What a test sees once its code has run: how it ended, the frame it
+    // ran in and the world state it left behind.
+    struct Run {
+        result: Result<TaskResult, VMError>,
+        frame: Frame,
+        state: WorldState,
+    }
+    fn run_in(env: &Env, code: &[u8], message: Message, mut state: WorldState) -> Run {
+        let input = InputManager::from_bytes(code.to_vec());
+        let mut task = Task::new(input, message, env, &mut state);
+        let result = task.execute();
+        let frame = task.frame;
+        Run {
+            result,
+            frame,
+            state,
+        }
+    }
+    fn run_with(code: &[u8], message: Message, state: WorldState) -> Run {
+        run_in(&Env::default(), code, message, state)
+    }
+    fn run_with_gas(code: &[u8], gas_limit: u64) -> Run {
+        let message = Message {
+            gas_limit,
+            ..Message::default()
+        };
+        run_with(code, message, WorldState::default())
+    }
+    fn run(code: &[u8]) -> Run {
+        run_with_gas(code, 100_000)
+    }
     #[test]
     fn iszero_works() {
         let instruction = OP_ISZERO;
         let arg_option = None;
+        let env = Env::default();
+        let mut state = WorldState::default();
         let message = Message::default();
         let mut task = Task {
-            message: &message,
-            stack: Stack {
-                values: vec![UInt256::ZERO],
+            env: &env,
+            state: &mut state,
+            frame: Frame {
+                message,
+                stack: Stack {
+                    values: vec![UInt256::ZERO],
+                },
+                memory: Memory::default(),
+                input: InputManager::from_bytes(vec![]),
+                gas: Gas::default(),
+                return_data: Vec::new(),
+                is_static: false,
             },
-            memory: Memory::default(),
-            input: InputManager::from_bytes(vec![]),
-            storage: Storage::default(),
+            callers: Vec::new(),
         };
         let result = task.execute_single_instruction(&instruction, arg_option);
         assert!(result.is_ok());
-        assert_eq!(task.stack.peek(0).expect("ok"), UInt256::ONE);
-        assert_eq!(task.stack.values.len(), 1);
+        assert_eq!(task.frame.stack.peek(0).expect("ok"), UInt256::ONE);
+        assert_eq!(task.frame.stack.values.len(), 1);
+    }
+    #[test]
+    fn signed_ops_use_stack_order() {
+        // SDIV computes top / second: PUSH1 2, -8, SDIV. -8 is PUSH1 8,
+        // PUSH1 0, SUB.
+        // SLT computes top < second: PUSH1 1, -8, SLT.
+        let after = run(&[
+            0x60, 0x02, 0x60, 0x08, 0x60, 0x00, 0x03, 0x05, 0x60, 0x01, 0x60, 0x08, 0x60, 0x00,
+            0x03, 0x12,
+        ]);
+        assert!(after.result.is_ok());
+        assert_eq!(
+            after.frame.stack.peek(1).expect("ok"),
+            UInt256::from_u128(4).negate()
+        );
+        assert_eq!(after.frame.stack.peek(0).expect("ok"), UInt256::ONE);
+    }
+    #[test]
+    fn dup_and_swap_reach_sixteen_deep() {
+        // PUSH1 1 up to PUSH1 `count`, then `instruction`.
+        let code = |count: u8, instruction: &Instruction| -> Vec<u8> {
+            let mut code: Vec<u8> = (1..=count).flat_map(|value| vec![0x60, value]).collect();
+            code.push(instruction.op);
+            code
+        };
+        // DUP16 copies the bottom item, 1, to the top.
+        let after = run(&code(16, &OP_DUP16));
+        assert_eq!(after.frame.stack.peek(0).expect("ok"), UInt256::ONE);
+        assert_eq!(after.frame.stack.values.len(), 17);
+        // SWAP15 exchanges the top, 16, with that same bottom item.
+        let after = run(&code(16, &OP_SWAP15));
+        assert_eq!(after.frame.stack.peek(0).expect("ok"), UInt256::ONE);
+        assert_eq!(after.frame.stack.peek(15).expect("ok"), UInt256::from(16u8));
+        // SWAP16 needs 17 items and DUP16 needs 16.
+        for (count, instruction) in [(16, &OP_SWAP16), (15, &OP_DUP16)].iter() {
+            assert_eq!(
+                run(&code(*count, instruction)).result.err(),
+                Some(VMError::StackUnderflow)
+            );
+        }
+    }
+    #[test]
+    fn stack_overflows_past_1024_items() {
+        // PUSH1 1 until the stack is full, then DUP1.
+        let mut code = [0x60, 0x01].repeat(STACK_LIMIT);
+        code.push(OP_DUP1.op);
+        let after = run(&code);
+        assert_eq!(after.result.err(), Some(VMError::StackOverflow));
+        assert_eq!(after.frame.stack.values.len(), STACK_LIMIT);
+        assert!(after.frame.stack.values.capacity() >= STACK_LIMIT);
+        // Ops that pop before pushing still fit.
+        code.pop();
+        code.push(OP_ADD.op);
+        assert!(run(&code).result.is_ok());
+    }
+    #[test]
+    fn jumps_only_land_on_jumpdest() {
+        // PUSH1 0x5B, JUMPDEST, PUSH1 0x01, JUMP
+        let after = run(&[0x60, 0x5B, 0x5B, 0x60, 0x01, 0x56]);
+        assert!(after.frame.input.is_jump_dest(2));
+        // Byte 1 is 0x5B, but it is PUSH1 data.
+        assert!(!after.frame.input.is_jump_dest(1));
+        assert_eq!(
+            after.result.err(),
+            Some(VMError::InvalidJump {
+                from: 5,
+                to: UInt256::ONE
+            })
+        );
+    }
+    #[test]
+    fn execution_charges_gas_until_exhausted() {
+        // JUMPDEST, GAS, POP, PUSH1 0x00, JUMP: an infinite loop.
+        let after = run_with_gas(&[0x5B, 0x5A, 0x50, 0x60, 0x00, 0x56], 100);
+        assert_eq!(after.result.err(), Some(VMError::OutOfGas));
+        assert_eq!(after.frame.gas.used(), 100);
+
+        // JUMPDEST, GAS, STOP: GAS sees what is left after JUMPDEST and
+        // its own cost.
+        let after = run_with_gas(&[0x5B, 0x5A, 0x00], 100);
+        assert!(after.result.is_ok());
+        assert_eq!(after.frame.stack.peek(0).expect("ok"), UInt256::from(97u8));
+        assert_eq!(after.frame.gas.used(), 3);
+    }
+    #[test]
+    fn memory_reads_zeros_and_charges_for_expansion() {
+        let mut memory = Memory::default();
+        let mut gas = Gas::new(1_000);
+        // Reading unwritten memory yields zero and grows memory to cover it.
+        let offset = UInt256::from(40u8);
+        assert_eq!(memory.load(&mut gas, offset), Ok(UInt256::ZERO));
+        assert_eq!(memory.size(), 96);
+        assert_eq!(gas.used(), 9);
+        // Touching memory already paid for costs nothing more.
+        memory
+            .store(&mut gas, UInt256::ZERO, UInt256::ONE)
+            .expect("ok");
+        assert_eq!(gas.used(), 9);
+        // 32 words cost 3 * 32 + 32 * 32 / 512 in total.
+        memory
+            .copy_out(&mut gas, UInt256::ZERO, UInt256::from(1024u16))
+            .expect("ok");
+        assert_eq!(gas.used(), 98);
+        // A zero-length access at any offset is free.
+        assert_eq!(
+            memory
+                .copy_out(&mut gas, !UInt256::ZERO, UInt256::ZERO)
+                .expect("ok"),
+            Vec::<u8>::new()
+        );
+        assert_eq!(
+            memory.load(&mut gas, !UInt256::ZERO),
+            Err(VMError::OutOfGas)
+        );
+    }
+    #[test]
+    fn sha3_hashes_memory_and_charges_per_word() {
+        // PUSH1 1, PUSH1 0, MSTORE, PUSH1 32, PUSH1 0, SHA3
+        let after = run(&[0x60, 0x01, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0x20]);
+        assert_eq!(
+            after.frame.stack.peek(0).expect("ok"),
+            UInt256::from(keccak256(&<[u8; 32]>::from(UInt256::ONE)))
+        );
+        // Four pushes, then MSTORE and the word of memory it grows, then
+        // SHA3 and the one word it hashes, with no further growth.
+        let gas_used = 4 * G_VERYLOW + G_VERYLOW + G_MEMORY + G_SHA3 + G_SHA3WORD;
+        assert_eq!(after.frame.gas.used(), gas_used);
+    }
+    #[test]
+    fn calldata_reads_past_the_end_as_zeros() {
+        // PUSH1 1, CALLDATALOAD, then CALLDATACOPY 4 bytes from offset 1
+        // into memory at 2.
+        let code = [0x60, 0x01, 0x35, 0x60, 0x04, 0x60, 0x01, 0x60, 0x02, 0x37];
+        let message = Message {
+            data: vec![0xAA, 0xBB],
+            gas_limit: 100_000,
+            ..Message::default()
+        };
+        let after = run_with(&code, message, WorldState::default());
+        let mut expected = [0u8; 32];
+        expected[0] = 0xBB;
+        assert_eq!(
+            after.frame.stack.peek(0).expect("ok"),
+            UInt256::from(expected)
+        );
+        assert_eq!(after.frame.memory.bytes[..7], [0, 0, 0xBB, 0, 0, 0, 0]);
+    }
+    #[test]
+    fn mstore8_stores_the_low_byte() {
+        // PUSH2 0x1234, PUSH1 33, MSTORE8
+        let after = run(&[0x61, 0x12, 0x34, 0x60, 0x21, 0x53]);
+        assert_eq!(after.frame.memory.size(), 64);
+        assert_eq!(after.frame.memory.bytes[33], 0x34);
+    }
+    #[test]
+    fn environment_opcodes_read_env_and_message() {
+        let env = Env {
+            block: BlockEnv {
+                timestamp: 1_700_000_000,
+                number: 18_000_000,
+                ..BlockEnv::default()
+            },
+            tx: TxEnv {
+                origin: address_from(0xAA),
+                ..TxEnv::default()
+            },
+        };
+        let message = Message {
+            caller: address_from(0xBB),
+            gas_limit: 100_000,
+            ..Message::default()
+        };
+        let code = [OP_TIMESTAMP.op, OP_NUMBER.op, OP_ORIGIN.op, OP_CALLER.op];
+        let after = run_in(&env, &code, message, WorldState::default());
+        assert_eq!(
+            after.frame.stack.values,
+            vec![
+                UInt256::from(1_700_000_000u64),
+                UInt256::from(18_000_000u64),
+                UInt256::from(0xAAu8),
+                UInt256::from(0xBBu8),
+            ]
+        );
+    }
+    #[test]
+    fn mcopy_handles_overlapping_ranges() {
+        // PUSH4 0x01020304, PUSH1 0, MSTORE puts 1, 2, 3, 4 at 28..32.
+        // MCOPY then copies those 4 bytes to 30.
+        let after = run(&[
+            0x63, 0x01, 0x02, 0x03, 0x04, 0x60, 0x00, 0x52, 0x60, 0x04, 0x60, 0x1C, 0x60, 0x1E,
+            0x5E,
+        ]);
+        assert!(after.result.is_ok());
+        assert_eq!(after.frame.memory.bytes[28..34], [1, 2, 1, 2, 3, 4]);
+    }
+    #[test]
+    fn transient_storage_round_trips() {
+        // PUSH1 7, PUSH1 1, TSTORE, PUSH1 1, TLOAD
+        let after = run(&[0x60, 0x07, 0x60, 0x01, 0x5D, 0x60, 0x01, 0x5C]);
+        assert_eq!(after.frame.stack.peek(0).expect("ok"), UInt256::from(7u8));
+    }
+    #[test]
+    fn returndatacopy_past_the_end_halts() {
+        // PUSH2 0xAABB, PUSH1 0, MSTORE, PUSH1 2, PUSH1 30, RETURN
+        let callee = vec![
+            0x61, 0xAA, 0xBB, 0x60, 0x00, 0x52, 0x60, 0x02, 0x60, 0x1E, 0xF3,
+        ];
+        let mut state = WorldState::default();
+        state.set_code(address_from(0xCC), callee);
+        let message = Message {
+            gas_limit: 100_000,
+            ..Message::default()
+        };
+        for (offset, copies) in [(0u8, true), (1, false)] {
+            // Call it, then RETURNDATACOPY 2 bytes from `offset` to 32.
+            let mut code = calling_code(&OP_STATICCALL, 0xCC, None);
+            code.pop();
+            code.extend([0x60, 0x02, 0x60, offset, 0x60, 0x20, 0x3E]);
+            let after = run_with(&code, message.clone(), state.clone());
+            if copies {
+                assert!(after.result.is_ok());
+                assert_eq!(after.frame.memory.bytes[32..34], [0xAA, 0xBB]);
+            } else {
+                assert_eq!(after.result.err(), Some(VMError::OutOfBounds));
+            }
+        }
+    }
+    #[test]
+    fn logs_are_returned_and_dropped_on_revert() {
+        // PUSH1 0xAB, PUSH1 0, MSTORE8, PUSH1 0x07, PUSH1 1, PUSH1 0, LOG1
+        let mut code = vec![0x60, 0xAB, 0x60, 0x00, 0x53];
+        code.extend([0x60, 0x07, 0x60, 0x01, 0x60, 0x00, 0xA1]);
+        let env = Env::default();
+        // Deploy by returning the code from the init code, then run it:
+        // once ending in STOP, once in REVERT.
+        for (ending, expect_logs) in [(0x00, true), (0xFD, false)] {
+            let mut state = WorldState::default();
+            let mut runtime = code.clone();
+            runtime.extend([0x60, 0x00, 0x60, 0x00, ending]);
+            let mut init_code = vec![0x60, runtime.len() as u8, 0x60, 0x0C, 0x60, 0x00, 0x39];
+            init_code.extend([0x60, runtime.len() as u8, 0x60, 0x00, 0xF3]);
+            init_code.extend(runtime);
+            let deployment = Message {
+                gas_limit: 100_000,
+                data: init_code,
+                ..Message::default()
+            };
+            let address = create_contract(&env, &mut state, deployment)
+                .expect("ok")
+                .contract_address
+                .expect("ok");
+            let result = send_message_to_contract(
+                &env,
+                &mut state,
+                Message {
+                    address,
+                    gas_limit: 100_000,
+                    ..Message::default()
+                },
+            );
+            if expect_logs {
+                let logs = result.expect("ok").logs;
+                assert_eq!(
+                    logs,
+                    vec![Log {
+                        address,
+                        topics: vec![UInt256::from(7u8)],
+                        data: vec![0xAB],
+                    }]
+                );
+            } else {
+                assert!(matches!(
+                    result,
+                    Err(ContractError::Revert { data, .. }) if data.is_empty()
+                ));
+            }
+        }
+    }
+    // Calls `target` with `value` (CALL and CALLCODE only) and all
+    // available gas, copying 96 bytes of output to memory, then stops.
+    fn calling_code(call: &Instruction, target: u8, value: Option<u8>) -> Vec<u8> {
+        // PUSH1 96, PUSH1 0, PUSH1 0, PUSH1 0
+        let mut code = vec![0x60, 0x60, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00];
+        if let Some(value) = value {
+            code.extend([0x60, value]);
+        }
+        // PUSH1 target, GAS, <call>, STOP
+        code.extend([0x60, target, 0x5A, call.op, 0x00]);
+        code
+    }
+    fn word_at(memory: &Memory, index: usize) -> UInt256 {
+        UInt256::from_be_slice(&memory.bytes[index * 32..(index + 1) * 32])
+    }
+    #[test]
+    fn call_flavours_set_caller_address_and_value() {
+        // Returns CALLER, ADDRESS and CALLVALUE as three words.
+        let reporter = vec![
+            0x33, 0x60, 0x00, 0x52, 0x30, 0x60, 0x20, 0x52, 0x34, 0x60, 0x40, 0x52, 0x60, 0x60,
+            0x60, 0x00, 0xF3,
+        ];
+        let (origin, us, them) = (0x11u8, 0xAAu8, 0xCCu8);
+        for (call, value, expected, balances) in [
+            (&OP_CALL, Some(3), [us, them, 3], [7u8, 3]),
+            (&OP_CALLCODE, Some(3), [us, us, 3], [10, 0]),
+            (&OP_DELEGATECALL, None, [origin, us, 5], [10, 0]),
+            (&OP_STATICCALL, None, [us, them, 0], [10, 0]),
+        ] {
+            let mut state = WorldState::default();
+            state.set_code(address_from(them), reporter.clone());
+            state.set_balance(address_from(us), UInt256::from(10u8));
+            let message = Message {
+                value: UInt256::from(5u8),
+                caller: address_from(origin),
+                address: address_from(us),
+                gas_limit: 100_000,
+                ..Message::default()
+            };
+            let after = run_with(&calling_code(call, them, value), message, state);
+            assert!(after.result.is_ok());
+            assert_eq!(after.frame.stack.peek(0).expect("ok"), UInt256::ONE);
+            for (index, &expected) in expected.iter().enumerate() {
+                assert_eq!(word_at(&after.frame.memory, index), UInt256::from(expected));
+            }
+            assert_eq!(after.frame.return_data.len(), 96);
+            for (&address, &balance) in [us, them].iter().zip(balances.iter()) {
+                assert_eq!(
+                    after.state.balance(address_from(address)),
+                    UInt256::from(balance)
+                );
+            }
+        }
+    }
+    #[test]
+    fn failed_calls_roll_back_and_push_zero() {
+        let (us, them) = (0xAAu8, 0xCCu8);
+        // A callee that reverts keeps its unused gas but loses its value
+        // transfer. One that writes inside a STATICCALL halts, burning all
+        // of the gas it was given.
+        for (call, value, callee, burns_gas) in [
+            (&OP_CALL, Some(3), vec![0x60, 0x00, 0x60, 0x00, 0xFD], false),
+            (
+                &OP_STATICCALL,
+                None,
+                vec![0x60, 0x01, 0x60, 0x00, 0x5D, 0x00],
+                true,
+            ),
+        ] {
+            let mut state = WorldState::default();
+            state.set_code(address_from(them), callee);
+            state.set_balance(address_from(us), UInt256::from(10u8));
+            let message = Message {
+                address: address_from(us),
+                gas_limit: 100_000,
+                ..Message::default()
+            };
+            let after = run_with(&calling_code(call, them, value), message, state);
+            assert!(after.result.is_ok());
+            assert_eq!(after.frame.stack.peek(0).expect("ok"), UInt256::ZERO);
+            assert_eq!(after.frame.gas.used() > 90_000, burns_gas);
+            assert_eq!(after.state.balance(address_from(us)), UInt256::from(10u8));
+            assert_eq!(
+                after
+                    .state
+                    .transient_storage(address_from(them), UInt256::ZERO),
+                UInt256::ZERO
+            );
+        }
+    }
+    #[test]
+    fn callee_gets_all_but_one_64th_of_the_gas() {
+        // GAS, PUSH1 0, MSTORE, PUSH1 32, PUSH1 0, RETURN
+        let callee = vec![0x5A, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xF3];
+        let mut state = WorldState::default();
+        state.set_code(address_from(0xCC), callee);
+        let message = Message {
+            gas_limit: 100_000,
+            ..Message::default()
+        };
+        let after = run_with(&calling_code(&OP_STATICCALL, 0xCC, None), message, state);
+        assert!(after.result.is_ok());
+        // Five pushes and GAS, then STATICCALL to a cold account and three
+        // words of memory.
+        let remaining: u64 = 100_000 - 17 - G_COLDACCOUNTACCESS - 9;
+        // The callee's GAS costs 2 before it reads what is left.
+        let forwarded = remaining - remaining / 64;
+        assert_eq!(
+            word_at(&after.frame.memory, 0),
+            UInt256::from(forwarded - 2)
+        );
+        // Whatever the callee didn't use comes back.
+        assert!(after.frame.gas.used() < 3_000);
+    }
+    #[test]
+    fn calls_past_the_depth_limit_fail() {
+        let env = Env::default();
+        let mut state = WorldState::default();
+        let message = Message::default();
+        let mut task = Task::new(InputManager::from_bytes(vec![]), message, &env, &mut state);
+        task.frame.gas = Gas::new(10_000);
+        for _ in 0..CALL_DEPTH_LIMIT {
+            task.callers.push(SuspendedFrame {
+                frame: Frame::default(),
+                kind: FrameKind::Call { return_range: 0..0 },
+                checkpoint: task.state.checkpoint(),
+            });
+        }
+        for expected in [UInt256::ZERO, UInt256::ONE] {
+            // STATICCALL 0xCC with 1000 gas and no input or output.
+            for arg in [0u16, 0, 0, 0, 0xCC, 1_000] {
+                task.frame.stack.push(UInt256::from(arg)).expect("ok");
+            }
+            assert!(task
+                .execute_single_instruction(&OP_STATICCALL, None)
+                .is_ok());
+            if expected == UInt256::ONE {
+                // The callee has no code, so it stops straight away.
+                task.return_to_caller(Ok(InstructionResult::Stop));
+            }
+            assert_eq!(task.frame.stack.pop().expect("ok"), expected);
+            // Either way the forwarded gas comes back unused, leaving only
+            // the first, cold, access to 0xCC paid for.
+            assert_eq!(task.frame.gas.used(), G_COLDACCOUNTACCESS - G_WARMACCESS);
+            task.callers.pop();
+        }
+    }
+    // Stores `init_code` (at most 32 bytes) at the end of the first word
+    // of memory, then runs CREATE, or CREATE2 with `salt`, and stops.
+    fn creating_code(init_code: &[u8], salt: Option<u8>) -> Vec<u8> {
+        let length = init_code.len() as u8;
+        let mut code = vec![0x5F + length];
+        code.extend_from_slice(init_code);
+        code.extend([0x60, 0x00, 0x52]);
+        if let Some(salt) = salt {
+            code.extend([0x60, salt]);
+        }
+        // PUSH1 length, PUSH1 offset, PUSH1 0 (value), <create>, STOP
+        let create = if salt.is_some() {
+            &OP_CREATE2
+        } else {
+            &OP_CREATE
+        };
+        code.extend([0x60, length, 0x60, 32 - length, 0x60, 0x00, create.op, 0x00]);
+        code
+    }
+    // PUSH1 0x2A, PUSH1 0, MSTORE8, PUSH1 1, PUSH1 0, RETURN: deploys 0x2A.
+    const DEPLOYS_2A: [u8; 10] = [0x60, 0x2A, 0x60, 0x00, 0x53, 0x60, 0x01, 0x60, 0x00, 0xF3];
+    #[test]
+    fn create_and_create2_deploy_at_derived_addresses() {
+        let factory = address_from(0xAA);
+        for salt in [None, Some(7u8)] {
+            let mut state = WorldState::default();
+            let code = creating_code(&DEPLOYS_2A, salt);
+            // Run the factory twice: CREATE moves on to the next nonce, but
+            // CREATE2 with the same salt collides with its first contract.
+            for nonce in 0..2 {
+                let message = Message {
+                    address: factory,
+                    gas_limit: 100_000,
+                    ..Message::default()
+                };
+                let after = run_with(&code, message, state);
+                assert!(after.result.is_ok());
+                let expected = match salt {
+                    Some(salt) if nonce == 0 => {
+                        UInt256::from(create2_address(factory, UInt256::from(salt), &DEPLOYS_2A))
+                    }
+                    Some(_) => UInt256::ZERO,
+                    None => UInt256::from(create_address(factory, nonce)),
+                };
+                assert_eq!(after.frame.stack.peek(0).expect("ok"), expected);
+                assert!(after.frame.return_data.is_empty());
+                state = after.state;
+            }
+            assert_eq!(state.nonce(factory), 2);
+            let first = match salt {
+                Some(salt) => create2_address(factory, UInt256::from(salt), &DEPLOYS_2A),
+                None => create_address(factory, 0),
+            };
+            assert_eq!(state.code(first), [0x2A]);
+            assert_eq!(state.nonce(first), 1);
+        }
+    }
+    #[test]
+    fn oversized_deployments_fail() {
+        // PUSH2 24577, PUSH1 0, RETURN
+        let init_code = [0x61, 0x60, 0x01, 0x60, 0x00, 0xF3];
+        let message = Message {
+            address: address_from(0xAA),
+            gas_limit: 100_000,
+            ..Message::default()
+        };
+        let after = run_with(
+            &creating_code(&init_code, None),
+            message,
+            WorldState::default(),
+        );
+        assert!(after.result.is_ok());
+        assert_eq!(after.frame.stack.peek(0).expect("ok"), UInt256::ZERO);
+        // The init code's gas is gone, but the factory's nonce still moved.
+        assert!(after.frame.gas.used() > 90_000);
+        let address = create_address(address_from(0xAA), 0);
+        assert!(after.state.is_empty(address));
+        assert_eq!(after.state.nonce(address_from(0xAA)), 1);
+    }
+    #[test]
+    fn sstore_is_priced_against_the_original_value() {
+        let env = Env::default();
+        let contract = address_from(0xCC);
+        let set_and_clear = G_TRANSACTION + 12 + G_COLDSLOAD + G_SSET + G_WARMACCESS;
+        // Each case writes a slot and then puts back what it held.
+        for (code, gas_used) in [
+            // PUSH1 0, PUSH1 0, SSTORE, PUSH1 5, PUSH1 0, SSTORE, STOP
+            (
+                [
+                    0x60, 0x00, 0x60, 0x00, 0x55, 0x60, 0x05, 0x60, 0x00, 0x55, 0x00,
+                ],
+                // A cold reset, then a warm write refunding all but 100.
+                G_TRANSACTION + 12 + G_COLDSLOAD + G_SRESET + G_WARMACCESS
+                    - (G_SRESET - G_WARMACCESS),
+            ),
+            // PUSH1 7, PUSH1 1, SSTORE, PUSH1 0, PUSH1 1, SSTORE, STOP
+            (
+                [
+                    0x60, 0x07, 0x60, 0x01, 0x55, 0x60, 0x00, 0x60, 0x01, 0x55, 0x00,
+                ],
+                // A cold set, then a warm write whose refund is capped at a
+                // fifth of the gas used.
+                set_and_clear - set_and_clear / 5,
+            ),
+        ]
+        .iter()
+        {
+            let mut state = WorldState::default();
+            state.set_code(contract, code.to_vec());
+            state.set_storage(contract, UInt256::ZERO, UInt256::from(5u8));
+            let message = Message {
+                address: contract,
+                gas_limit: 100_000,
+                ..Message::default()
+            };
+            let result = send_message_to_contract(&env, &mut state, message);
+            assert_eq!(result.expect("ok").gas_used, *gas_used);
+            assert_eq!(state.storage(contract, UInt256::ZERO), UInt256::from(5u8));
+            assert_eq!(state.storage(contract, UInt256::ONE), UInt256::ZERO);
+        }
+    }
+    #[test]
+    fn failed_transactions_report_gas_used() {
+        let env = Env::default();
+        let mut state = WorldState::default();
+        let (reverts, halts) = (address_from(0xAA), address_from(0xBB));
+        // PUSH1 0, PUSH1 0, REVERT
+        state.set_code(reverts, vec![0x60, 0x00, 0x60, 0x00, 0xFD]);
+        // INVALID
+        state.set_code(halts, vec![0xFE]);
+        let message = |address| Message {
+            address,
+            gas_limit: 100_000,
+            ..Message::default()
+        };
+        assert_eq!(
+            send_message_to_contract(&env, &mut state, message(reverts)).err(),
+            Some(ContractError::Revert {
+                data: vec![],
+                gas_used: G_TRANSACTION + 6
+            })
+        );
+        // An exceptional halt burns everything it was given.
+        assert_eq!(
+            send_message_to_contract(&env, &mut state, message(halts)).err(),
+            Some(ContractError::InternalError {
+                error: VMError::BadOp(0xFE),
+                gas_used: 100_000
+            })
+        );
+    }
+    #[test]
+    fn create_transactions_deploy_or_leave_nothing_behind() {
+        let env = Env::default();
+        let mut state = WorldState::default();
+        let sender = address_from(0x11);
+        // PUSH1 0, PUSH1 0, REVERT
+        for (init_code, deploys) in [
+            (DEPLOYS_2A.to_vec(), true),
+            (vec![0x60, 0x00, 0x60, 0x00, 0xFD], false),
+        ] {
+            let nonce = state.nonce(sender);
+            let message = Message {
+                caller: sender,
+                gas_limit: 100_000,
+                data: init_code,
+                ..Message::default()
+            };
+            let result = create_contract(&env, &mut state, message);
+            let address = create_address(sender, nonce);
+            if deploys {
+                assert_eq!(result.expect("ok").contract_address, Some(address));
+                assert_eq!(state.code(address), [0x2A]);
+            } else {
+                // Creation, five bytes of init code and two pushes.
+                let gas_used = G_TRANSACTION + G_TXCREATE + 3 * 16 + 2 * 4 + 2 + 6;
+                assert_eq!(
+                    result.err(),
+                    Some(ContractError::Revert {
+                        data: vec![],
+                        gas_used
+                    })
+                );
+                assert!(state.is_empty(address));
+            }
+            assert_eq!(state.nonce(sender), nonce + 1);
+        }
+    }
+    #[test]
+    fn selfdestruct_deletes_only_contracts_created_in_the_same_transaction() {
+        let env = Env::default();
+        let mut state = WorldState::default();
+        let sender = address_from(0x11);
+        let heir = address_from(0xBE);
+        state.set_balance(sender, UInt256::from(10u8));
+        // PUSH1 0xBE, SELFDESTRUCT, run as init code and then deployed by
+        // PUSH3 <it>, PUSH1 0, MSTORE, PUSH1 3, PUSH1 29, RETURN.
+        let destructs = vec![0x60, 0xBE, 0xFF];
+        let mut deploys_destructs = vec![0x62];
+        deploys_destructs.extend(&destructs);
+        deploys_destructs.extend([0x60, 0x00, 0x52, 0x60, 0x03, 0x60, 0x1D, 0xF3]);
+        let creating = |data| Message {
+            caller: sender,
+            value: UInt256::from(5u8),
+            gas_limit: 200_000,
+            data,
+            ..Message::default()
+        };
+        // Destroyed by its own init code, the account is gone afterwards.
+        let result = create_contract(&env, &mut state, creating(destructs.clone()));
+        let address = result.expect("ok").contract_address.expect("a create");
+        assert!(state.account(address).is_none());
+        assert_eq!(state.balance(heir), UInt256::from(5u8));
+        // Destroyed in a later transaction, it only loses its balance.
+        let result = create_contract(&env, &mut state, creating(deploys_destructs));
+        let address = result.expect("ok").contract_address.expect("a create");
+        let message = Message {
+            caller: sender,
+            address,
+            gas_limit: 100_000,
+            ..Message::default()
+        };
+        assert!(send_message_to_contract(&env, &mut state, message).is_ok());
+        assert_eq!(state.code(address), destructs.as_slice());
+        assert_eq!(state.balance(address), UInt256::ZERO);
+        assert_eq!(state.balance(heir), UInt256::from(10u8));
+    }
+    #[test]
+    fn account_opcodes_read_the_world_state() {
+        let (us, them) = (address_from(0xAA), address_from(0xCC));
+        let mut state = WorldState::default();
+        state.set_code(them, vec![0x00, 0x2A]);
+        state.set_balance(them, UInt256::from(9u8));
+        state.set_balance(us, UInt256::from(3u8));
+        state.set_storage(us, UInt256::ONE, UInt256::from(5u8));
+        let message = Message {
+            address: us,
+            gas_limit: 100_000,
+            ..Message::default()
+        };
+        // PUSH1 1, SLOAD, PUSH1 them, BALANCE, SELFBALANCE,
+        // PUSH1 them, EXTCODESIZE, PUSH1 them, EXTCODEHASH,
+        // PUSH1 1, EXTCODEHASH, then EXTCODECOPY 3 bytes of them from
+        // offset 1 into memory at 0.
+        let code = [
+            0x60, 0x01, 0x54, 0x60, 0xCC, 0x31, 0x47, 0x60, 0xCC, 0x3B, 0x60, 0xCC, 0x3F, 0x60,
+            0x01, 0x3F, 0x60, 0x03, 0x60, 0x01, 0x60, 0x00, 0x60, 0xCC, 0x3C,
+        ];
+        let after = run_with(&code, message, state);
+        assert_eq!(
+            after.frame.stack.values,
+            vec![
+                UInt256::from(5u8),
+                UInt256::from(9u8),
+                UInt256::from(3u8),
+                UInt256::from(2u8),
+                UInt256::from(keccak256(&[0x00, 0x2A])),
+                UInt256::ZERO,
+            ]
+        );
+        assert_eq!(after.frame.memory.bytes[..3], [0x2A, 0, 0]);
+    }
+    #[test]
+    fn calls_store_in_their_storage_context() {
+        // PUSH1 1, PUSH1 1, SSTORE, STOP
+        let callee = vec![0x60, 0x01, 0x60, 0x01, 0x55, 0x00];
+        let (us, them) = (address_from(0xAA), address_from(0xCC));
+        for (call, writer) in [(&OP_CALL, them), (&OP_DELEGATECALL, us)] {
+            let mut state = WorldState::default();
+            state.set_code(them, callee.clone());
+            let message = Message {
+                address: us,
+                gas_limit: 100_000,
+                ..Message::default()
+            };
+            let value = if *call == OP_CALL { Some(0) } else { None };
+            let after = run_with(&calling_code(call, 0xCC, value), message, state);
+            assert!(after.result.is_ok());
+            for address in [us, them] {
+                assert_eq!(
+                    after.state.storage(address, UInt256::ONE),
+                    UInt256::from_bool(address == writer)
+                );
+            }
+        }
+    }
+    #[test]
+    fn running_off_the_end_of_the_code_stops() {
+        let env = Env::default();
+        let mut state = WorldState::default();
+        let callee = address_from(0xCC);
+        // PUSH1 1, PUSH1 1, SSTORE, with no STOP after it.
+        state.set_code(callee, vec![0x60, 0x01, 0x60, 0x01, 0x55]);
+        let message = Message {
+            gas_limit: 100_000,
+            ..Message::default()
+        };
+        let after = run_with(&calling_code(&OP_CALL, 0xCC, Some(0)), message, state);
+        assert!(after.result.is_ok());
+        assert_eq!(after.frame.stack.peek(0).expect("ok"), UInt256::ONE);
+        assert!(after.frame.gas.used() < 30_000);
+        assert_eq!(after.state.storage(callee, UInt256::ONE), UInt256::ONE);
+        let mut state = after.state;
+        let message = Message {
+            address: callee,
+            gas_limit: 100_000,
+            ..Message::default()
+        };
+        assert!(send_message_to_contract(&env, &mut state, message).is_ok());
+    }
+    #[test]
+    fn cut_off_push_data_reads_as_zeros() {
+        // PUSH2 0xAB, missing its second byte.
+        let after = run_with_gas(&[0x61, 0xAB], 100);
+        assert!(after.result.is_ok());
+        assert_eq!(
+            after.frame.stack.peek(0).expect("ok"),
+            UInt256::from(0xAB00u16)
+        );
+        assert_eq!(after.frame.gas.used(), G_VERYLOW);
     }
 }
@@ -0,0 +1,198 @@
+// Differential tests checking every UInt256 operation against num-bigint,
+// reduced mod 2^256 (or interpreted as two's complement for signed ops).
+
+use num_bigint::{BigInt, BigUint, Sign};
+use proptest::prelude::*;
+use rusty_ethereum::UInt256;
+
+fn modulus() -> BigUint {
+    BigUint::from(1u8) << 256
+}
+
+fn to_big(value: UInt256) -> BigUint {
+    let bytes: [u8; 32] = value.into();
+    BigUint::from_bytes_be(&bytes)
+}
+
+fn from_big(value: BigUint) -> UInt256 {
+    UInt256::from_be_slice(&(value % modulus()).to_bytes_be())
+}
+
+fn to_signed(value: UInt256) -> BigInt {
+    let unsigned = BigInt::from_biguint(Sign::Plus, to_big(value));
+    if value.is_negative() {
+        unsigned - BigInt::from_biguint(Sign::Plus, modulus())
+    } else {
+        unsigned
+    }
+}
+
+fn from_signed(value: BigInt) -> UInt256 {
+    let modulus = BigInt::from_biguint(Sign::Plus, modulus());
+    let reduced = ((value % &modulus) + &modulus) % &modulus;
+    from_big(reduced.to_biguint().unwrap())
+}
+
+// Uniformly random words almost never hit the interesting cases (zero,
+// single-limb values, INT_MIN, all ones), so mix those in explicitly and
+// truncate random words to every possible width.
+fn word() -> impl Strategy<Value = UInt256> {
+    prop_oneof![
+        any::<[u8; 32]>().prop_map(UInt256::from),
+        (any::<[u8; 32]>(), 0..256u32)
+            .prop_map(|(bytes, bits)| UInt256::from(bytes) >> UInt256::from(bits)),
+        any::<u64>().prop_map(UInt256::from),
+        Just(UInt256::ZERO),
+        Just(UInt256::ONE),
+        Just(!UInt256::ZERO),
+        Just(UInt256::INT_MIN),
+    ]
+}
+
+fn shift() -> impl Strategy<Value = UInt256> {
+    prop_oneof![(0..300u32).prop_map(UInt256::from), word()]
+}
+
+// Shift amounts past 256 all behave like 256, which keeps the oracle from
+// allocating enormous intermediates.
+fn shift_bits(shift: UInt256) -> usize {
+    if shift >= UInt256::from(256u32) {
+        256
+    } else {
+        shift.limbs()[0] as usize
+    }
+}
+
+proptest! {
+    #[test]
+    fn add_matches(a in word(), b in word()) {
+        prop_assert_eq!(a + b, from_big(to_big(a) + to_big(b)));
+        prop_assert_eq!(a.overflowing_add(b).1, to_big(a) + to_big(b) >= modulus());
+    }
+
+    #[test]
+    fn sub_matches(a in word(), b in word()) {
+        prop_assert_eq!(a - b, from_big(to_big(a) + modulus() - to_big(b)));
+        prop_assert_eq!(a.overflowing_sub(b).1, a < b);
+    }
+
+    #[test]
+    fn mul_matches(a in word(), b in word()) {
+        prop_assert_eq!(a * b, from_big(to_big(a) * to_big(b)));
+    }
+
+    #[test]
+    fn div_rem_match(a in word(), b in word()) {
+        let (quotient, remainder) = if b == UInt256::ZERO {
+            (UInt256::ZERO, UInt256::ZERO)
+        } else {
+            (from_big(to_big(a) / to_big(b)), from_big(to_big(a) % to_big(b)))
+        };
+        prop_assert_eq!(a / b, quotient);
+        prop_assert_eq!(a % b, remainder);
+    }
+
+    #[test]
+    fn signed_div_rem_match(a in word(), b in word()) {
+        // BigInt division truncates toward zero and the remainder takes the
+        // dividend's sign, as SDIV and SMOD require. INT_MIN / -1 wraps.
+        let (quotient, remainder) = if b == UInt256::ZERO {
+            (UInt256::ZERO, UInt256::ZERO)
+        } else {
+            (
+                from_signed(to_signed(a) / to_signed(b)),
+                from_signed(to_signed(a) % to_signed(b)),
+            )
+        };
+        prop_assert_eq!(a.signed_div(b), quotient);
+        prop_assert_eq!(a.signed_rem(b), remainder);
+    }
+
+    #[test]
+    fn comparisons_match(a in word(), b in word()) {
+        prop_assert_eq!(a.cmp(&b), to_big(a).cmp(&to_big(b)));
+        prop_assert_eq!(a.signed_cmp(b), to_signed(a).cmp(&to_signed(b)));
+    }
+
+    #[test]
+    fn shifts_match(a in word(), shift in shift()) {
+        let bits = shift_bits(shift);
+        prop_assert_eq!(a << shift, from_big(to_big(a) << bits));
+        prop_assert_eq!(a >> shift, from_big(to_big(a) >> bits));
+        // BigInt's right shift rounds toward negative infinity, which is
+        // exactly the sign fill SAR performs.
+        prop_assert_eq!(a.sar(shift), from_signed(to_signed(a) >> bits));
+    }
+
+    #[test]
+    fn pow_matches(base in word(), exponent in word()) {
+        prop_assert_eq!(
+            base.pow(exponent),
+            from_big(to_big(base).modpow(&to_big(exponent), &modulus()))
+        );
+    }
+
+    #[test]
+    fn add_mod_mul_mod_match(a in word(), b in word(), m in word()) {
+        let (sum, product) = if m == UInt256::ZERO {
+            (UInt256::ZERO, UInt256::ZERO)
+        } else {
+            (
+                from_big((to_big(a) + to_big(b)) % to_big(m)),
+                from_big((to_big(a) * to_big(b)) % to_big(m)),
+            )
+        };
+        prop_assert_eq!(a.add_mod(b, m), sum);
+        prop_assert_eq!(a.mul_mod(b, m), product);
+    }
+
+    #[test]
+    fn byte_matches(a in word(), index in 0..40usize) {
+        let expected = if index < 32 {
+            let shifted: BigUint = to_big(a) >> ((31 - index) * 8);
+            (shifted % 256u32).to_bytes_be()[0]
+        } else {
+            0
+        };
+        prop_assert_eq!(a.byte(index), expected);
+    }
+
+    #[test]
+    fn sign_extend_matches(a in word(), byte_index in 0..40u32) {
+        let expected = if byte_index >= 31 {
+            a
+        } else {
+            // Reinterpret the low (byte_index + 1) bytes as a signed value.
+            let bits = (byte_index as usize + 1) * 8;
+            let low = to_big(a) % (BigUint::from(1u8) << bits);
+            let low = BigInt::from_biguint(Sign::Plus, low);
+            if low.bit(bits as u64 - 1) {
+                from_signed(low - (BigInt::from(1u8) << bits))
+            } else {
+                from_signed(low)
+            }
+        };
+        prop_assert_eq!(a.sign_extend(UInt256::from(byte_index)), expected);
+    }
+
+    #[test]
+    fn decimal_round_trips(a in word()) {
+        let decimal = a.to_string_radix(10);
+        prop_assert_eq!(&decimal, &to_big(a).to_str_radix(10));
+        prop_assert_eq!(UInt256::from_str_radix(&decimal, 10).unwrap(), a);
+    }
+}
+
+#[test]
+fn oracle_round_trips_edge_values() {
+    for value in [
+        UInt256::ZERO,
+        UInt256::ONE,
+        !UInt256::ZERO,
+        UInt256::INT_MIN,
+    ] {
+        assert_eq!(from_big(to_big(value)), value);
+        assert_eq!(from_signed(to_signed(value)), value);
+    }
+    assert_eq!(to_signed(!UInt256::ZERO), BigInt::from(-1));
+}